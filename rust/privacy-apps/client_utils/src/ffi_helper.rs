@@ -28,16 +28,18 @@ use std::ffi::{ c_char, CStr, CString };
 /// - `Ok(String)`: On success, returns a `String` containing the converted C string.
 /// - `Err(String)`: On failure, returns a `String` containing an error message.
 ///
-pub fn c_str_to_string(c_str: *const c_char) -> Result<String, String> {
+/// # Safety
+///
+/// `c_str` must be null or point to a NUL-terminated string that stays valid during the call.
+///
+pub unsafe fn c_str_to_string(c_str: *const c_char) -> Result<String, String> {
     if c_str.is_null() {
         return Err("Received null pointer".to_string());
     }
-    unsafe {
-        CStr::from_ptr(c_str)
-            .to_str()
-            .map(|s| s.to_string())
-            .map_err(|e| format!("Failed to convert C string to Rust string: {}", e))
-    }
+    CStr::from_ptr(c_str)
+        .to_str()
+        .map(|s| s.to_string())
+        .map_err(|e| format!("Failed to convert C string to Rust string: {}", e))
 }
 
 ///
@@ -72,7 +74,7 @@ mod tests {
         let c_string = CString::new(original).unwrap();
         let c_str = c_string.as_ptr();
 
-        let result = c_str_to_string(c_str);
+        let result = unsafe { c_str_to_string(c_str) };
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), original);
     }
@@ -81,7 +83,7 @@ mod tests {
     fn test_c_str_to_string_null_pointer() {
         let c_str: *const c_char = std::ptr::null();
 
-        let result = c_str_to_string(c_str);
+        let result = unsafe { c_str_to_string(c_str) };
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Received null pointer".to_string());
     }
@@ -92,7 +94,7 @@ mod tests {
         let c_string = CString::new(&invalid_utf8[..]).unwrap();
         let c_str = c_string.as_ptr();
 
-        let result = c_str_to_string(c_str);
+        let result = unsafe { c_str_to_string(c_str) };
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Failed to convert C string to Rust string"));
     }
@@ -104,7 +106,7 @@ mod tests {
         let result = string_to_cstring(original.clone());
         assert!(result.is_success());
 
-        let converted_back = c_str_to_string(unsafe { *result.result }).unwrap();
+        let converted_back = unsafe { c_str_to_string(*result.result) }.unwrap();
         assert_eq!(converted_back, original);
     }
 
@@ -114,7 +116,7 @@ mod tests {
 
         let result = string_to_cstring(original);
         assert!(!result.is_success());
-        let err = c_str_to_string(result.error).unwrap();
+        let err = unsafe { c_str_to_string(result.error) }.unwrap();
         assert!(err.contains("nul byte found in provided data"));
     }
 }
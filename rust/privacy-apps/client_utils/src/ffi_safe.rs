@@ -80,7 +80,7 @@ mod tests {
     #[test]
     fn test_from_result_value() {
         let ffi_result = FfiResult::<u32>::from_result_value(42);
-        assert_eq!(ffi_result.is_success(), true);
+        assert!(ffi_result.is_success());
         assert_eq!(unsafe { *ffi_result.result }, 42);
         assert_eq!(ffi_result.error, ptr::null());
     }
@@ -88,7 +88,7 @@ mod tests {
     #[test]
     fn test_from_error_message() {
         let ffi_result = FfiResult::<u32>::from_error_message("Error message".to_string());
-        assert_eq!(ffi_result.is_success(), false);
+        assert!(!ffi_result.is_success());
         assert_eq!(unsafe { CStr::from_ptr(ffi_result.error).to_str().unwrap() }, "Error message");
         assert_eq!(ffi_result.result, ptr::null());
     }
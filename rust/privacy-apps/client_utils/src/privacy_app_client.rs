@@ -154,7 +154,7 @@ impl PrivacyAppClient {
             // - Success: it will return 200, then return the "output"
             // - Pending / Processing: it will return response code of 202 wait for `TIMEOUT_IN_SECONDS` seconds and retry
             // - Failed: it will return HTTP response based on the correspond ZkPassServiceError.
-            match self.get_proof_generation_status::<Res>(&queue_status_url).await {
+            match self.get_proof_generation_status::<Res>(queue_status_url).await {
                 Ok(result) => {
                     return Ok(result);
                 }
@@ -232,7 +232,7 @@ async fn http_response_to_result<Res: DeserializeOwned>(
                             )
                         })?;
                     let expected_string: Res = serde_json
-                        ::from_str(&expected_value_str)
+                        ::from_str(expected_value_str)
                         .map_err(|_| {
                             ClientLibError::ServerResponseNotOk(
                                 status,
@@ -11,6 +11,7 @@ use jsonwebtoken::{ decode, DecodingKey, Header, Validation };
 use serde::{ Deserialize, Serialize };
 use serde_json::{ json, Value };
 use std::collections::HashMap;
+use zkpass_query_types::{ QueryEngineError, ZkPassQueryEngineError };
 use crate::privacy_apps::interface::{ KeysetEndpoint, PublicKeyOption };
 use super::{
     interface::{
@@ -144,7 +145,12 @@ fn validate_user_data_requests(
 ) -> Result<bool, ZkPassError> {
     if user_data_requests.is_empty() {
         return Err(
-            ZkPassError::QueryEngineError(String::from("User data requests must not be empty"))
+            ZkPassError::QueryEngineError(
+                QueryEngineError::new(
+                    ZkPassQueryEngineError::UserDataParsingError,
+                    "User data requests must not be empty"
+                )
+            )
         );
     }
 
@@ -152,9 +158,10 @@ fn validate_user_data_requests(
         if key.is_empty() && user_data_requests.len() > 1 {
             return Err(
                 ZkPassError::QueryEngineError(
-                    String::from(
+                    QueryEngineError::new(
+                        ZkPassQueryEngineError::UserDataParsingError,
                         "User data request tag must not be empty if there are more than one"
-                    )
+                    ).with_token(key)
                 )
             );
         }
@@ -162,9 +169,10 @@ fn validate_user_data_requests(
         if !key.chars().all(|c| (c.is_ascii_alphanumeric() || c == '_')) {
             return Err(
                 ZkPassError::QueryEngineError(
-                    String::from(
+                    QueryEngineError::new(
+                        ZkPassQueryEngineError::UserDataParsingError,
                         "User data request tag must only contain alphanumeric characters and underscores"
-                    )
+                    ).with_token(key)
                 )
             );
         }
@@ -258,7 +266,32 @@ mod test {
                 String::from("user_data_2$") => mock_user_data(),
             };
 
-        assert!(validate_user_data_requests(&user_data_requests).is_err());
+        let err = validate_user_data_requests(&user_data_requests).unwrap_err();
+        match &err {
+            ZkPassError::QueryEngineError(err) => {
+                assert_eq!(err.kind, ZkPassQueryEngineError::UserDataParsingError);
+                assert_eq!(err.token.as_deref(), Some("user_data_2$"));
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        assert_eq!(
+            err.to_string(),
+            "UserDataParsingError: User data request tag must only contain alphanumeric characters and underscores"
+        );
+    }
+
+    #[test]
+    fn test_query_engine_error_kind_into_zkpass_error() {
+        let err = ZkPassError::from(ZkPassQueryEngineError::CyclesLimitExceededError);
+        match &err {
+            ZkPassError::QueryEngineError(err) => {
+                assert_eq!(err.kind, ZkPassQueryEngineError::CyclesLimitExceededError);
+                assert_eq!(err.location, None);
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        assert_eq!(err.to_string(), "CyclesLimitExceededError: the cycles limit of the zkvm is exceeded");
     }
 
     #[test]
@@ -15,6 +15,7 @@ use serde_json::Value;
 use sha2::{ Digest, Sha256 };
 use std::collections::HashMap;
 use thiserror::Error;
+use zkpass_query_types::{ QueryEngineError, ZkPassQueryEngineError };
 
 use crate::privacy_apps::interface::{ PublicKey, PublicKeyOption, ZkPassPrivacyAppError };
 
@@ -74,11 +75,21 @@ pub enum ZkPassError {
     #[error("Invalid ZKVM name")]
     InvalidZkVm,
 
-    #[error("{0}")] QueryEngineError(String),
+    /// A query engine error, located in the query when possible.
+    /// It is displayed as its kind, location and message; the wrapped error keeps the token,
+    /// and `QueryEngineError::to_json` serializes all of them for the FFI consumers.
+    #[error("{0}")] QueryEngineError(#[from] QueryEngineError),
 }
 
 impl ZkPassPrivacyAppError for ZkPassError {}
 
+// The errors of the zkvm query engine adapters are only a kind, without a location.
+impl From<ZkPassQueryEngineError> for ZkPassError {
+    fn from(kind: ZkPassQueryEngineError) -> Self {
+        ZkPassError::QueryEngineError(kind.into())
+    }
+}
+
 ///
 /// <span style="font-size: 1.1em; color: #996515;"> ***The value returned by the `generate_zkpass_proof` API of the zkPass Service.*** </span>
 ///
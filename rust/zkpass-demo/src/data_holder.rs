@@ -14,12 +14,21 @@ use crate::{
     lib_loader::generate_zkpass_proof,
 };
 use client_utils::interface::PrivacyAppCredentialsFfi;
+use serde::Deserialize;
 use std::{ collections::HashMap, time::Instant, ffi::CString };
-use tracing::info;
+use tracing::{ error, info };
 use zkpass_query_types::OutputReader;
 
 pub struct DataHolder;
 
+///
+/// The outputs of the demo dvrs that the Data Holder relies on.
+///
+#[derive(Deserialize)]
+struct QueryResult {
+    result: bool,
+}
+
 impl DataHolder {
     ///
     /// Starts the Data Holder process.
@@ -37,7 +46,16 @@ impl DataHolder {
         //  Get the dvr from the verifier
         //
         let mut proof_verifier = ProofVerifier::default();
-        let dvr_token = proof_verifier.get_dvr_token(zkvm, dvr_file, user_data_tags_list);
+        let dvr_token = match proof_verifier.get_dvr_token(zkvm, dvr_file, user_data_tags_list) {
+            Ok(dvr_token) => dvr_token,
+            Err(errors) => {
+                for err in &errors {
+                    error!("{}", err);
+                }
+                error!("Error: the dvr query is rejected by the proof verifier");
+                return;
+            }
+        };
 
         let zkpass_service_url = std::env
             ::var("ZKPASS_URL")
@@ -76,8 +94,10 @@ impl DataHolder {
         }
         println!("<< end of list");
 
-        let val = output_reader.find_bool("result").unwrap();
-        println!("the query result is {}", val);
+        match output_reader.into_typed::<QueryResult>() {
+            Ok(query_result) => println!("the query result is {}", query_result.result),
+            Err(err) => error!("Error: the query result cannot be read: {}", err),
+        }
     }
 
     ///
@@ -57,11 +57,7 @@ impl DataIssuer {
     /// Signs the user data token.
     ///
     fn sign_user_data_token(&self, data: Value) -> String {
-        let user_data_token = unsafe {
-            generate_user_data_token(ISSUER_PRIVKEY, &data.to_string())
-        };
-
-        user_data_token
+        unsafe { generate_user_data_token(ISSUER_PRIVKEY, &data.to_string()) }
     }
 
     ///
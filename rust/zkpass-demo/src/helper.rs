@@ -9,7 +9,7 @@
  * Copyright (c) 2024 PT Darta Media Indonesia. All rights reserved.
  */
 use core::panic;
-use std::{ collections::HashMap, fs };
+use std::{ collections::{ hash_map::Entry, HashMap }, fs };
 use tracing::info;
 use base64::{ decode_config, URL_SAFE };
 use serde_json::Value;
@@ -83,14 +83,18 @@ pub fn extract_user_data_tags(user_data_paths: &Vec<&str>) -> HashMap<String, St
                 if paths_count == 1 {
                     extracted_user_data_tags.insert(tag, user_data_file);
                 } else {
-                    if extracted_user_data_tags.contains_key(&tag) {
-                        panic!("Error: tag '{}' is defined twice", tag);
-                    } else if tag.is_empty() || user_data_file.is_empty() {
-                        panic!(
-                            "Error: When defining multiple user data, 'tags' for each user-data-file must be specified"
-                        );
-                    } else {
-                        extracted_user_data_tags.insert(tag, user_data_file);
+                    match extracted_user_data_tags.entry(tag) {
+                        Entry::Occupied(entry) => {
+                            panic!("Error: tag '{}' is defined twice", entry.key());
+                        }
+                        Entry::Vacant(entry) => {
+                            if entry.key().is_empty() || user_data_file.is_empty() {
+                                panic!(
+                                    "Error: When defining multiple user data, 'tags' for each user-data-file must be specified"
+                                );
+                            }
+                            entry.insert(user_data_file);
+                        }
                     }
                 }
             }
@@ -100,7 +104,7 @@ pub fn extract_user_data_tags(user_data_paths: &Vec<&str>) -> HashMap<String, St
         }
     }
 
-    return extracted_user_data_tags;
+    extracted_user_data_tags
 }
 
 ///
@@ -159,14 +163,9 @@ pub fn pick_example_data_and_dvr(example_name: &str) -> (HashMap<String, String>
     };
     info!("Overiding user data and dvr file with example data");
 
-    let user_data_tags = extract_user_data_tags(
-        &user_data_tags
-            .iter()
-            .map(|x| x.as_ref())
-            .collect()
-    );
+    let user_data_tags = extract_user_data_tags(&user_data_tags);
 
-    return (user_data_tags, dvr_file_path);
+    (user_data_tags, dvr_file_path)
 }
 
 #[cfg(test)]
@@ -177,7 +176,7 @@ mod tests {
     const DUMMY_DATA: &str = r#"{"name": "Alice", "age": 25}"#;
 
     fn copy_all_test_files() -> Vec<PathBuf> {
-        let source_dirs = vec!["../test/data", "../test/data/multiple"];
+        let source_dirs = ["../test/data", "../test/data/multiple"];
         let current_dir = std::env::current_dir().unwrap();
         let target_dirs = vec![
             current_dir.join("test/data"),
@@ -199,7 +198,7 @@ mod tests {
                     let file_name = path.file_name().unwrap();
 
                     // Copy to the first target directory
-                    let destination_first = target_dir.join(&file_name);
+                    let destination_first = target_dir.join(file_name);
                     fs::copy(&path, &destination_first).unwrap();
                     copied_files.push(destination_first);
                 }
@@ -223,16 +222,16 @@ mod tests {
     #[test]
     fn test_path_exists_and_validate_path() {
         let path = "./test_path.json";
-        std::fs::write(&path, DUMMY_DATA).expect("Unable to write file");
+        std::fs::write(path, DUMMY_DATA).expect("Unable to write file");
         validate_path("validate", path);
-        assert_eq!(path_exists(path), true);
+        assert!(path_exists(path));
         std::fs::remove_file(path).expect("Unable to remove file");
     }
 
     #[test]
     fn test_extract_user_data_tags_single() {
         let path = "./extract_user_data.json";
-        std::fs::write(&path, DUMMY_DATA).expect("Unable to write file");
+        std::fs::write(path, DUMMY_DATA).expect("Unable to write file");
 
         let user_data_paths = vec!["./extract_user_data.json"];
         let extracted_user_data_tags = extract_user_data_tags(&user_data_paths);
@@ -249,8 +248,8 @@ mod tests {
     fn test_extract_user_data_tags_multiple() {
         let path1 = "./extract_user_data_1.json";
         let path2 = "./extract_user_data_2.json";
-        std::fs::write(&path1, DUMMY_DATA).expect("Unable to write file");
-        std::fs::write(&path2, DUMMY_DATA).expect("Unable to write file");
+        std::fs::write(path1, DUMMY_DATA).expect("Unable to write file");
+        std::fs::write(path2, DUMMY_DATA).expect("Unable to write file");
 
         let user_data_paths = vec![
             "tag1:./extract_user_data_1.json",
@@ -110,7 +110,7 @@ fn main() {
     let rt = Runtime::new().unwrap();
 
     let example = matches.value_of("pick-example").unwrap_or("");
-    if example != "" {
+    if !example.is_empty() {
         let (user_data_tags, dvr_file_path) = pick_example_data_and_dvr(example);
         rt.block_on(run_data_holder(zkvm_type, user_data_tags, dvr_file_path));
     } else {
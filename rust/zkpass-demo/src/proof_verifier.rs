@@ -32,6 +32,7 @@ use serde_json::Value;
 use std::{ collections::HashMap, io::prelude::*, sync::Mutex, time::Instant, ffi::CString };
 use tracing::trace;
 use uuid::Uuid;
+use zkpass_query_types::{ parse_query_for_engine, QueryEngineError };
 
 //
 //  Global table to store the generated DVR values
@@ -68,18 +69,11 @@ struct UserDataRequestsHolder {
 //
 //  Simulating the Proof Verifier
 //
+#[derive(Default)]
 pub struct ProofVerifier {
     pub user_data_tags: Vec<String>,
 }
 
-impl Default for ProofVerifier {
-    fn default() -> Self {
-        ProofVerifier {
-            user_data_tags: Vec::new(),
-        }
-    }
-}
-
 impl ProofVerifier {
     ///
     /// Generates the user data requests.
@@ -104,7 +98,7 @@ impl ProofVerifier {
 
         Box::new(UserDataRequestsHolder {
             tags,
-            user_data_requests: user_data_requests,
+            user_data_requests,
             key_x: issuer_public_key_option_holder.key_x,
             key_y: issuer_public_key_option_holder.key_y,
             empty_str: issuer_public_key_option_holder.empty_str,
@@ -114,18 +108,16 @@ impl ProofVerifier {
 
     ///
     /// Simulates the Proof Verifier's get_dvr_token REST API.
+    /// The query is rejected with its errors, before the DVR is signed,
+    /// when it is malformed or uses a part of the language that the proof method does not run.
     ///
     pub fn get_dvr_token(
         &mut self,
         zkvm: &str,
         dvr_file: &str,
         user_data_tags: Vec<&String>
-    ) -> String {
-        self.user_data_tags = user_data_tags
-            .iter()
-            .cloned()
-            .map(|s| s.clone())
-            .collect();
+    ) -> Result<String, Vec<QueryEngineError>> {
+        self.user_data_tags = user_data_tags.into_iter().cloned().collect();
 
         let mut query_content = std::fs::File::open(dvr_file).expect("Cannot find the dvr file");
         let mut query = String::new();
@@ -141,6 +133,9 @@ impl ProofVerifier {
 
         let query_string = serde_json::to_string(&query).unwrap();
 
+        // Reject the query before the DVR is signed
+        parse_query_for_engine(&query_string)?;
+
         let zkvm_cstring = CString::new(zkvm).unwrap();
         let dvr_title_cstring = CString::new("My DVR").unwrap();
         let dvr_id_cstring = CString::new(Uuid::new_v4().to_string()).unwrap();
@@ -181,7 +176,7 @@ impl ProofVerifier {
             dvr_table.insert(dvr_id.to_string(), data.to_string());
         }
 
-        dvr_token
+        Ok(dvr_token)
     }
 
     ///
@@ -22,6 +22,7 @@ mod tests {
             base_url: CString::new("base_url").unwrap().into_raw(),
             api_key: CString::new("api_key").unwrap().into_raw(),
             secret_api_key: CString::new("secret_api_key").unwrap().into_raw(),
+            using_queue: false,
         };
 
         let _data_issuer = DataIssuer;
@@ -12,7 +12,7 @@ mod e2e_tests {
             initial_command.push_str("cd ../ && ");
         }
         initial_command.push_str("export LD_LIBRARY_PATH=./lib:$LD_LIBRARY_PATH");
-        return initial_command.to_string();
+        initial_command.to_string()
     }
 
     fn run_demo_and_verify_output(
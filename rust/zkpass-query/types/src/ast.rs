@@ -0,0 +1,484 @@
+//
+// Typed abstract syntax tree of the zkPass query language.
+//
+// A query is parsed into these types by `parser::parse_query`.
+// The types derive serde, so a parsed query can be handed over to an evaluator
+// (e.g. inside the zkvm) without being parsed again.
+//
+use serde::{Deserialize, Serialize};
+
+use crate::text::TextOptions;
+use crate::{Decimal, Val};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Query {
+    pub statements: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum Statement {
+    /// `{ "assign": { "<name>": <expression> } }`
+    Assign { name: String, expression: Expression },
+
+    /// `{ "output": { "<name>": <expression> } }`
+    Output { name: String, expression: Expression },
+
+    /// `{ "if": { "condition": <expression>, "then": [...], "else": [...] } }`
+    If {
+        condition: Expression,
+        then_block: Vec<Statement>,
+        else_block: Vec<Statement>,
+    },
+
+    /// `{ "define": { "<name>": <expression> | { "params": ["<param>"...], "body": <expression> }... } }`,
+    /// named sub-expressions declared at the top of the query, which may refer to each other
+    Define { definitions: Vec<Definition> },
+
+    /// `{ "switch": { "cases": [{ "condition": <expression>, "then": [...] }...], "default": [...] } }`,
+    /// runs the block of the first case whose condition is true, or the default block when none is
+    Switch {
+        cases: Vec<Case>,
+        default_block: Vec<Statement>,
+    },
+
+    /// `{ "const": { "<name>": [<literals>...] } }`, a named list declared at the top of the query
+    Const { name: String, values: LiteralSet },
+
+    /// `{ "text": { "normalization": "none" | "nfc" | "nfkc", "case_folding": "full" | "turkic" } }`,
+    /// the options of the string comparisons, declared as the first statement of the query
+    Text(TextOptions),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Case {
+    pub condition: Expression,
+    pub block: Vec<Statement>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Expression,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum Expression {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    Array(Vec<Expression>),
+
+    /// A number literal with a fraction, e.g. `0.4`
+    Decimal(Decimal),
+
+    /// `{ "dvar": "<path>" }`, a value of the user data
+    DataVar(String),
+
+    /// `{ "dvar": "<path>", "default": <expression> }`, a value of the user data,
+    /// or the default when the path is missing, but not when it is present with a null value
+    DataVarOrDefault {
+        path: String,
+        default: Box<Expression>,
+    },
+
+    /// `{ "lvar": "<name>" }`, a variable created by an assign statement
+    LocalVar(String),
+
+    /// `{ "ivar": "<path>" }`, a value of the current element of an array operator,
+    /// e.g. "amount" or "address.city", or "@" for the element itself
+    ItemVar(String),
+
+    /// `{ "const": "<name>" }`, a list declared by a const statement
+    Constant(String),
+
+    /// `{ "ref": "<name>" }` or `{ "ref": ["<name>", <arguments>...] }`, the value of a definition,
+    /// with its parameters bound to the arguments
+    Reference {
+        name: String,
+        args: Vec<Expression>,
+    },
+
+    /// `{ "param": "<name>" }`, a parameter of the definition being evaluated
+    Param(String),
+
+    /// A literal array of an `in` or `not_in` operator, sorted as a set when the query is parsed
+    Set(LiteralSet),
+
+    /// `{ "<operator>": [<left>, <right>, "and" | "or"] }`
+    Comparison {
+        operator: Operator,
+        left: Box<Expression>,
+        right: Box<Expression>,
+        quantifier: Quantifier,
+    },
+
+    /// `{ "<operator>": [<operands>...] }`
+    Operation {
+        operator: Operator,
+        operands: Vec<Expression>,
+    },
+}
+
+///
+/// The quantifier applied when a comparison has an array operand.
+///
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum Quantifier {
+    /// "and": the comparison must hold for all elements (the default)
+    All,
+
+    /// "or": the comparison must hold for at least one element
+    Any,
+}
+
+///
+/// A set of literals, sorted once so that the membership of a value is a binary search.
+/// A set is deserialized through `LiteralSet::new`, so it is always sorted and without duplicates.
+///
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(try_from = "RawLiteralSet")]
+pub struct LiteralSet {
+    literals: Vec<Literal>,
+}
+
+// The serialized literals of a set, before they are sorted.
+#[derive(Deserialize)]
+#[serde(rename = "LiteralSet")]
+struct RawLiteralSet {
+    literals: Vec<Literal>,
+}
+
+impl From<RawLiteralSet> for LiteralSet {
+    fn from(raw: RawLiteralSet) -> Self {
+        LiteralSet::new(raw.literals)
+    }
+}
+
+impl LiteralSet {
+    pub fn new(mut literals: Vec<Literal>) -> Self {
+        literals.sort();
+        literals.dedup();
+        LiteralSet { literals }
+    }
+
+    pub fn contains(&self, literal: &Literal) -> bool {
+        self.literals.binary_search(literal).is_ok()
+    }
+
+    // Converts the set into an array of its literals, in their sorted order.
+    pub fn to_val(&self) -> Val {
+        Val::Array(self.literals.iter().map(Literal::to_val).collect())
+    }
+}
+
+///
+/// A literal of a `LiteralSet`. The numbers are decimals, so 2 and 2.0 are the same literal.
+///
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Literal {
+    Bool(bool),
+    Number(Decimal),
+    Str(String),
+}
+
+impl Literal {
+    // Converts a string, a number or a boolean into a literal.
+    pub fn from_val(val: &Val) -> Option<Self> {
+        match val {
+            Val::Bool(b) => Some(Literal::Bool(*b)),
+            Val::Int(i) => Some(Literal::Number(Decimal::from_i64(*i))),
+            Val::Decimal(d) => Some(Literal::Number(*d)),
+            Val::Str(s) => Some(Literal::Str(s.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn to_val(&self) -> Val {
+        match self {
+            Literal::Bool(b) => Val::Bool(*b),
+            Literal::Number(d) => d.to_i64().map(Val::Int).unwrap_or(Val::Decimal(*d)),
+            Literal::Str(s) => Val::Str(s.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    EqIgnoreCase,
+    ContainsIgnoreCase,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    And,
+    Or,
+    GetAge,
+    GetDayDiff,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    AgeYears,
+    DaysBetween,
+    DateBefore,
+    DateAfter,
+    HoursSince,
+    HoursBetween,
+    Filter,
+    Map,
+    Count,
+    Sum,
+    Min,
+    Max,
+    AnyOf,
+    AllOf,
+    NoneOf,
+    SubsetOf,
+    SupersetOf,
+    Intersects,
+    Length,
+    StartsWith,
+    StartsWithIgnoreCase,
+    EndsWith,
+    EndsWithIgnoreCase,
+    Contains,
+    Matches,
+    MatchesIgnoreCase,
+    Substring,
+    In,
+    NotIn,
+    Exists,
+    IsNull,
+    Bucket,
+    RoundDown,
+    Commit,
+    Pseudonym,
+    Mask,
+    Similar,
+    Similarity,
+    BlindedIn,
+}
+
+impl Operator {
+    // Gets the operator from its symbol in the query.
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        let operator = match symbol {
+            "==" => Operator::Eq,
+            "~==" => Operator::EqIgnoreCase,
+            "~contains" => Operator::ContainsIgnoreCase,
+            ">" => Operator::Gt,
+            ">=" => Operator::Ge,
+            "<" => Operator::Lt,
+            "<=" => Operator::Le,
+            "and" => Operator::And,
+            "or" => Operator::Or,
+            "get_age" => Operator::GetAge,
+            "get_day_diff" => Operator::GetDayDiff,
+            "+" => Operator::Add,
+            "-" => Operator::Sub,
+            "*" => Operator::Mul,
+            "/" => Operator::Div,
+            "%" => Operator::Rem,
+            "age_years" => Operator::AgeYears,
+            "days_between" => Operator::DaysBetween,
+            "date_before" => Operator::DateBefore,
+            "date_after" => Operator::DateAfter,
+            "hours_since" => Operator::HoursSince,
+            "hours_between" => Operator::HoursBetween,
+            "filter" => Operator::Filter,
+            "map" => Operator::Map,
+            "count" => Operator::Count,
+            "sum" => Operator::Sum,
+            "min" => Operator::Min,
+            "max" => Operator::Max,
+            "any" => Operator::AnyOf,
+            "all" => Operator::AllOf,
+            "none" => Operator::NoneOf,
+            "subset_of" => Operator::SubsetOf,
+            "superset_of" => Operator::SupersetOf,
+            "intersects" => Operator::Intersects,
+            "length" => Operator::Length,
+            "starts_with" => Operator::StartsWith,
+            "~starts_with" => Operator::StartsWithIgnoreCase,
+            "ends_with" => Operator::EndsWith,
+            "~ends_with" => Operator::EndsWithIgnoreCase,
+            "contains" => Operator::Contains,
+            "matches" => Operator::Matches,
+            "~matches" => Operator::MatchesIgnoreCase,
+            "substring" => Operator::Substring,
+            "in" => Operator::In,
+            "not_in" => Operator::NotIn,
+            "exists" => Operator::Exists,
+            "is_null" => Operator::IsNull,
+            "bucket" => Operator::Bucket,
+            "round_down" => Operator::RoundDown,
+            "commit" => Operator::Commit,
+            "pseudonym" => Operator::Pseudonym,
+            "mask" => Operator::Mask,
+            "similar" => Operator::Similar,
+            "similarity" => Operator::Similarity,
+            "blinded_in" => Operator::BlindedIn,
+            _ => {
+                return None;
+            }
+        };
+        Some(operator)
+    }
+
+    // Gets the symbol of the operator in the query.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Eq => "==",
+            Operator::EqIgnoreCase => "~==",
+            Operator::ContainsIgnoreCase => "~contains",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::And => "and",
+            Operator::Or => "or",
+            Operator::GetAge => "get_age",
+            Operator::GetDayDiff => "get_day_diff",
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::Rem => "%",
+            Operator::AgeYears => "age_years",
+            Operator::DaysBetween => "days_between",
+            Operator::DateBefore => "date_before",
+            Operator::DateAfter => "date_after",
+            Operator::HoursSince => "hours_since",
+            Operator::HoursBetween => "hours_between",
+            Operator::Filter => "filter",
+            Operator::Map => "map",
+            Operator::Count => "count",
+            Operator::Sum => "sum",
+            Operator::Min => "min",
+            Operator::Max => "max",
+            Operator::AnyOf => "any",
+            Operator::AllOf => "all",
+            Operator::NoneOf => "none",
+            Operator::SubsetOf => "subset_of",
+            Operator::SupersetOf => "superset_of",
+            Operator::Intersects => "intersects",
+            Operator::Length => "length",
+            Operator::StartsWith => "starts_with",
+            Operator::StartsWithIgnoreCase => "~starts_with",
+            Operator::EndsWith => "ends_with",
+            Operator::EndsWithIgnoreCase => "~ends_with",
+            Operator::Contains => "contains",
+            Operator::Matches => "matches",
+            Operator::MatchesIgnoreCase => "~matches",
+            Operator::Substring => "substring",
+            Operator::In => "in",
+            Operator::NotIn => "not_in",
+            Operator::Exists => "exists",
+            Operator::IsNull => "is_null",
+            Operator::Bucket => "bucket",
+            Operator::RoundDown => "round_down",
+            Operator::Commit => "commit",
+            Operator::Pseudonym => "pseudonym",
+            Operator::Mask => "mask",
+            Operator::Similar => "similar",
+            Operator::Similarity => "similarity",
+            Operator::BlindedIn => "blinded_in",
+        }
+    }
+
+    // Relational operators compare two operands, with an optional array quantifier.
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Operator::Eq
+                | Operator::EqIgnoreCase
+                | Operator::ContainsIgnoreCase
+                | Operator::StartsWith
+                | Operator::StartsWithIgnoreCase
+                | Operator::EndsWith
+                | Operator::EndsWithIgnoreCase
+                | Operator::Contains
+                | Operator::Matches
+                | Operator::MatchesIgnoreCase
+                | Operator::Gt
+                | Operator::Ge
+                | Operator::Lt
+                | Operator::Le
+        )
+    }
+
+    // Arithmetic operators compute an integer from integer operands.
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(self, Operator::Add | Operator::Sub | Operator::Mul | Operator::Div | Operator::Rem)
+    }
+
+    // Date operators take one or two dates, followed by the date format of the dates.
+    pub fn is_date(&self) -> bool {
+        matches!(
+            self,
+            Operator::GetAge
+                | Operator::GetDayDiff
+                | Operator::AgeYears
+                | Operator::DaysBetween
+                | Operator::DateBefore
+                | Operator::DateAfter
+                | Operator::HoursSince
+                | Operator::HoursBetween
+        )
+    }
+
+    // Array operators take an array, followed by a predicate or a mapping that is evaluated
+    // for each element of the array, with the element as the `ivar`.
+    pub fn is_array(&self) -> bool {
+        matches!(
+            self,
+            Operator::Filter
+                | Operator::Map
+                | Operator::Count
+                | Operator::Sum
+                | Operator::Min
+                | Operator::Max
+                | Operator::AnyOf
+                | Operator::AllOf
+                | Operator::NoneOf
+        )
+    }
+
+    // Set operators compare the elements of two arrays, regardless of their order and duplicates.
+    pub fn is_set(&self) -> bool {
+        matches!(self, Operator::SubsetOf | Operator::SupersetOf | Operator::Intersects)
+    }
+
+    // Membership operators check whether a value is in a list, given as a literal array,
+    // a const list or any expression of an array.
+    pub fn is_membership(&self) -> bool {
+        matches!(self, Operator::In | Operator::NotIn)
+    }
+
+    // Engine operators are the ones that the zkvm proof method runs, see `parse_query_for_engine`.
+    pub fn is_engine(&self) -> bool {
+        matches!(
+            self,
+            Operator::Eq
+                | Operator::EqIgnoreCase
+                | Operator::ContainsIgnoreCase
+                | Operator::Gt
+                | Operator::Ge
+                | Operator::Lt
+                | Operator::Le
+                | Operator::And
+                | Operator::Or
+                | Operator::GetAge
+                | Operator::GetDayDiff
+        )
+    }
+
+    // Presence operators check a single dvar or ivar, without failing when it is missing.
+    // A field that is present with a null value exists and is null; a missing field only is null.
+    pub fn is_presence(&self) -> bool {
+        matches!(self, Operator::Exists | Operator::IsNull)
+    }
+}
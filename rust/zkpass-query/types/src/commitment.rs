@@ -0,0 +1,88 @@
+//
+// Salted hash commitments of query values.
+//
+// The `commit` operator outputs `sha256(salt || canonical(value))` as a hex string, where the salt
+// is supplied privately by the data holder. The verifier learns nothing about the value until the
+// holder reveals the value and the salt, the opening, which `verify_opening` checks against the output.
+//
+// The `blinded_in` operator uses the same hashes the other way around: the verifier puts the salt and the
+// hashes of its expected values, made by `blind`, into the DVR instead of the values themselves.
+// The guest needs the salt to hash the dvar, so the holder and anyone else with the DVR token learn it,
+// and can hash candidate values with it until one matches. The hashes therefore only hide values that
+// are hard to guess: a country code, a date of birth, a hospital ID of a public registry or a short
+// document number is found by hashing every possible value, whatever the length of the salt.
+// A fresh salt for each DVR only keeps the same screening list from being recognized across DVRs.
+//
+use sha2::{ Digest, Sha256 };
+
+use crate::Val;
+
+// The minimum length of a salt in bytes, so a committed value with few possible values
+// cannot be found by hashing all of them while the holder keeps the salt. It does not protect
+// the hashes of `blinded_in`, whose salt is in the DVR.
+pub const MIN_SALT_LENGTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SaltError {
+    MalformedSaltError,
+    ShortSaltError,
+}
+
+// Decodes a hex salt of at least `MIN_SALT_LENGTH` bytes.
+pub fn parse_salt(salt: &str) -> Result<Vec<u8>, SaltError> {
+    let salt = hex::decode(salt).map_err(|_| SaltError::MalformedSaltError)?;
+    if salt.len() < MIN_SALT_LENGTH {
+        return Err(SaltError::ShortSaltError);
+    }
+    Ok(salt)
+}
+
+// Computes the hex commitment of a value with a salt.
+pub fn commit(salt: &[u8], val: &Val) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(canonical(val).as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+// Computes the hex hash of an expected value with a hex salt, for the `blinded_in` operator of a DVR.
+// The salt is disclosed with the DVR, so the hash of a value with few possible values does not hide it.
+pub fn blind(salt: &str, val: &Val) -> Result<String, SaltError> {
+    Ok(commit(&parse_salt(salt)?, val))
+}
+
+// Checks whether a string is a hex SHA-256 hash in lower case, like the hashes of `commit`.
+pub fn is_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+// Checks that a value and a hex salt open a commitment.
+// A malformed salt, or a commitment in upper case hex, opens nothing.
+pub fn verify_opening(commitment: &str, salt: &str, val: &Val) -> bool {
+    match parse_salt(salt) {
+        Ok(salt) => commit(&salt, val) == commitment,
+        Err(_) => false,
+    }
+}
+
+// Serializes a value into its canonical JSON: the JSON of the outputs, with the keys of the objects
+// sorted, so the same value has the same commitment whatever the order of the keys it was read with.
+// A decimal is in its plain notation without trailing zeros, e.g. 2.5, so it differs from the integer 2.
+pub fn canonical(val: &Val) -> String {
+    match val {
+        Val::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical).collect();
+            format!("[{}]", items.join(","))
+        }
+        Val::Object(entries) => {
+            let mut entries: Vec<_> = entries.iter().collect();
+            entries.sort_by(|a, b| a.key.cmp(&b.key));
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|entry| format!("{}:{}", Val::Str(entry.key.clone()).to_json(), canonical(&entry.val)))
+                .collect();
+            format!("{{{}}}", entries.join(","))
+        }
+        _ => val.to_json(),
+    }
+}
@@ -0,0 +1,360 @@
+//
+// Exact fixed-point decimal numbers of the zkPass query language.
+//
+// A `Decimal` is an integer number of units of 10^-scale, e.g. 12.345 is 12345 units at scale 3.
+// All the operations are done on integers, so the results do not depend on the floating point
+// behaviour of the host or the zkvm. A decimal is kept normalized, without trailing fractional
+// zeros, so that two equal decimals are also structurally equal.
+//
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::pointer;
+use crate::Val;
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum DecimalError {
+    MalformedDecimalError,
+    OverflowError,
+}
+
+impl fmt::Display for DecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecimalError::MalformedDecimalError => write!(f, "the decimal is malformed"),
+            DecimalError::OverflowError => write!(f, "the decimal overflows"),
+        }
+    }
+}
+
+// A decimal is deserialized through `Decimal::new`, so it is always normalized and within `MAX_SCALE`.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(try_from = "RawDecimal")]
+pub struct Decimal {
+    units: i128,
+    scale: u32,
+}
+
+// The name under which a decimal is deserialized, so that the output deserializer recognizes a `Decimal`
+// field by a name no application struct can have.
+pub(crate) const SERDE_NAME: &str = "$zkpass::Decimal";
+
+// The serialized fields of a decimal, before they are validated.
+#[derive(Deserialize)]
+#[serde(rename = "$zkpass::Decimal")]
+struct RawDecimal {
+    units: i128,
+    scale: u32,
+}
+
+impl TryFrom<RawDecimal> for Decimal {
+    type Error = DecimalError;
+
+    fn try_from(raw: RawDecimal) -> Result<Self, Self::Error> {
+        Decimal::new(raw.units, raw.scale)
+    }
+}
+
+impl Decimal {
+    // The maximum number of fractional digits.
+    // The products and quotients with more fractional digits are truncated toward zero.
+    pub const MAX_SCALE: u32 = 18;
+
+    // Creates a decimal of `units` * 10^-`scale`.
+    pub fn new(units: i128, scale: u32) -> Result<Self, DecimalError> {
+        if scale > Decimal::MAX_SCALE {
+            return Err(DecimalError::OverflowError);
+        }
+        Ok(Decimal { units, scale }.normalize())
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        Decimal { units: value as i128, scale: 0 }
+    }
+
+    pub fn units(&self) -> i128 {
+        self.units
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    // Converts the decimal to an i64 when it has no fractional digits.
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.scale == 0 { i64::try_from(self.units).ok() } else { None }
+    }
+
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = Decimal::align(self, other)?;
+        Some(Decimal { units: a.checked_add(b)?, scale }.normalize())
+    }
+
+    pub fn checked_sub(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = Decimal::align(self, other)?;
+        Some(Decimal { units: a.checked_sub(b)?, scale }.normalize())
+    }
+
+    pub fn checked_mul(&self, other: &Decimal) -> Option<Decimal> {
+        let units = self.units.checked_mul(other.units)?;
+        Decimal::truncate(units, self.scale + other.scale)
+    }
+
+    // Divides to `MAX_SCALE` fractional digits, truncating toward zero.
+    // Returns `None` on overflow or division by zero.
+    pub fn checked_div(&self, other: &Decimal) -> Option<Decimal> {
+        if other.units == 0 {
+            return None;
+        }
+        // (a / 10^sa) / (b / 10^sb) = (a * 10^(MAX_SCALE + sb - sa) / b) / 10^MAX_SCALE
+        let exponent = Decimal::MAX_SCALE + other.scale - self.scale;
+        let dividend = self.units.checked_mul(10i128.checked_pow(exponent)?)?;
+        Some(Decimal { units: dividend.checked_div(other.units)?, scale: Decimal::MAX_SCALE }.normalize())
+    }
+
+    // The remainder has the sign of the dividend, like the remainder of integers.
+    pub fn checked_rem(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = Decimal::align(self, other)?;
+        Some(Decimal { units: a.checked_rem(b)?, scale }.normalize())
+    }
+
+    // Rescales both decimals to the larger scale of the two.
+    fn align(a: &Decimal, b: &Decimal) -> Option<(i128, i128, u32)> {
+        let scale = a.scale.max(b.scale);
+        let a_units = a.units.checked_mul(10i128.checked_pow(scale - a.scale)?)?;
+        let b_units = b.units.checked_mul(10i128.checked_pow(scale - b.scale)?)?;
+        Some((a_units, b_units, scale))
+    }
+
+    // Drops the fractional digits beyond `MAX_SCALE`, truncating toward zero.
+    fn truncate(units: i128, scale: u32) -> Option<Decimal> {
+        if scale <= Decimal::MAX_SCALE {
+            return Some(Decimal { units, scale }.normalize());
+        }
+        // Beyond 38 dropped digits, nothing of an i128 is left
+        let units = match 10i128.checked_pow(scale - Decimal::MAX_SCALE) {
+            Some(divisor) => units / divisor,
+            None => 0,
+        };
+        Some(Decimal { units, scale: Decimal::MAX_SCALE }.normalize())
+    }
+
+    // Splits the decimal into its integer part, rounded toward negative infinity,
+    // and its non negative fraction in units of 10^-MAX_SCALE.
+    fn split(&self) -> (i128, i128) {
+        let divisor = 10i128.pow(self.scale);
+        let fraction = self.units.rem_euclid(divisor) * 10i128.pow(Decimal::MAX_SCALE - self.scale);
+        (self.units.div_euclid(divisor), fraction)
+    }
+
+    // Removes the trailing fractional zeros.
+    fn normalize(mut self) -> Self {
+        while self.scale > 0 && self.units % 10 == 0 {
+            self.units /= 10;
+            self.scale -= 1;
+        }
+        if self.units == 0 {
+            self.scale = 0;
+        }
+        self
+    }
+}
+
+// Compares the integer parts, then the fractions at `MAX_SCALE`, so that no rescaling can overflow.
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.split().cmp(&other.split())
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Formats the decimal in plain notation, e.g. "-0.05".
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.scale == 0 {
+            return write!(f, "{}", self.units);
+        }
+        let digits = self.units.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        let sign = if self.units < 0 { "-" } else { "" };
+        write!(f, "{}{}.{}", sign, integer, fraction)
+    }
+}
+
+// Parses a JSON number literal, e.g. "12.5", "-0.05" or "1.5e-7".
+// The fractional digits beyond `MAX_SCALE` are truncated toward zero.
+impl FromStr for Decimal {
+    type Err = DecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Decimal::parse(s, false)
+    }
+}
+
+impl Decimal {
+    // Parses a JSON number literal like `from_str`, but a literal with non-zero digits beyond `MAX_SCALE`
+    // is an overflow instead of being truncated, so the decimal has exactly the value of the literal.
+    pub fn from_str_exact(s: &str) -> Result<Self, DecimalError> {
+        Decimal::parse(s, true)
+    }
+
+    fn parse(s: &str, exact: bool) -> Result<Self, DecimalError> {
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(index) => {
+                let exponent: i32 = s[index + 1..].parse().map_err(|_| DecimalError::MalformedDecimalError)?;
+                (&s[..index], exponent)
+            }
+            None => (s, 0),
+        };
+        let (negative, mantissa) = match mantissa.strip_prefix('-') {
+            Some(mantissa) => (true, mantissa),
+            None => (false, mantissa),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        let empty_fraction = mantissa.contains('.') && fraction.is_empty();
+        if integer.is_empty() || empty_fraction || !is_digits(integer) || !is_digits(fraction) {
+            return Err(DecimalError::MalformedDecimalError);
+        }
+
+        let digits = format!("{}{}", integer, fraction);
+        let digits = digits.trim_start_matches('0');
+        let mut units: i128 = if digits.is_empty() {
+            0
+        } else {
+            digits.parse().map_err(|_| DecimalError::OverflowError)?
+        };
+        if negative {
+            units = -units;
+        }
+
+        let scale = fraction.len() as i64 - exponent as i64;
+        if scale < 0 {
+            let multiplier = u32::try_from(-scale)
+                .ok()
+                .and_then(|exponent| 10i128.checked_pow(exponent))
+                .ok_or(DecimalError::OverflowError)?;
+            let units = units.checked_mul(multiplier).ok_or(DecimalError::OverflowError)?;
+            return Ok(Decimal { units, scale: 0 });
+        }
+        let scale = u32::try_from(scale).map_err(|_| DecimalError::OverflowError)?;
+        let decimal = Decimal::truncate(units, scale).ok_or(DecimalError::OverflowError)?;
+        let is_truncated = scale > Decimal::MAX_SCALE &&
+            10i128.checked_pow(scale - Decimal::MAX_SCALE).map_or(units != 0, |divisor| units % divisor != 0);
+        if exact && is_truncated {
+            return Err(DecimalError::OverflowError);
+        }
+        Ok(decimal)
+    }
+}
+
+// Finds the number literals of a valid JSON text that are not read exactly, with their JSON pointers.
+// serde_json reads a number that is not a 64-bit integer through an f64, e.g. 12345678901234567.89
+// is read as 12345678901234568, so such a literal is reported rather than silently rounded.
+pub(crate) fn inexact_numbers(json: &str) -> Vec<(String, String)> {
+    let mut scanner = NumberScanner { json, pos: 0, found: Vec::new() };
+    scanner.scan_value("");
+    scanner.found
+}
+
+// Checks that a number literal is read as a value equal to the literal.
+fn is_exact(literal: &str) -> bool {
+    let read = match serde_json::from_str::<serde_json::Number>(literal).ok().and_then(|n| Val::from_number(&n)) {
+        Some(Val::Int(i)) => Decimal::from_i64(i),
+        Some(Val::Decimal(d)) => d,
+        _ => {
+            return false;
+        }
+    };
+    Decimal::from_str_exact(literal).is_ok_and(|exact| exact == read)
+}
+
+// Walks a JSON text that serde_json has already accepted, so it does not validate it again.
+struct NumberScanner<'a> {
+    json: &'a str,
+    pos: usize,
+    found: Vec<(String, String)>,
+}
+
+impl NumberScanner<'_> {
+    // Skips the whitespace and peeks at the next byte.
+    fn peek(&mut self) -> Option<u8> {
+        self.pos += self.json[self.pos..].bytes().take_while(u8::is_ascii_whitespace).count();
+        self.json.as_bytes().get(self.pos).copied()
+    }
+
+    // Skips the byte after the whitespace, e.g. a ':' or a ','.
+    fn skip(&mut self) {
+        self.peek();
+        self.pos += 1;
+    }
+
+    fn scan_value(&mut self, location: &str) {
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                while self.peek().is_some_and(|b| b != b'}') {
+                    let key = self.scan_string();
+                    self.skip();
+                    self.scan_value(&pointer(location, &key));
+                    if self.peek() == Some(b',') {
+                        self.pos += 1;
+                    }
+                }
+                self.pos += 1;
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut index = 0;
+                while self.peek().is_some_and(|b| b != b']') {
+                    self.scan_value(&pointer(location, &index.to_string()));
+                    index += 1;
+                    if self.peek() == Some(b',') {
+                        self.pos += 1;
+                    }
+                }
+                self.pos += 1;
+            }
+            Some(b'"') => {
+                self.scan_string();
+            }
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.pos;
+                self.pos += self.json[start..]
+                    .bytes()
+                    .take_while(|b| matches!(b, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'))
+                    .count();
+                let literal = &self.json[start..self.pos];
+                if !is_exact(literal) {
+                    self.found.push((location.to_string(), literal.to_string()));
+                }
+            }
+            // true, false or null
+            _ => {
+                self.pos += self.json[self.pos..].bytes().take_while(u8::is_ascii_alphabetic).count();
+            }
+        }
+    }
+
+    // Reads a string, e.g. a key, unescaped.
+    fn scan_string(&mut self) -> String {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(b) = self.json.as_bytes().get(self.pos) {
+            self.pos += if *b == b'\\' { 2 } else { 1 };
+            if *b == b'"' {
+                break;
+            }
+        }
+        serde_json::from_str(&self.json[start..self.pos.min(self.json.len())]).unwrap_or_default()
+    }
+}
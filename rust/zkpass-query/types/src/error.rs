@@ -0,0 +1,169 @@
+//
+// Structured errors of the zkPass query engine.
+//
+// A `QueryEngineError` wraps the `ZkPassQueryEngineError` kind with the location
+// of the offending element in the query, the offending token and a human message.
+// It is serializable, so it can be passed as JSON across the FFI boundary and shown
+// by the TypeScript and C consumers as well.
+//
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::ZkPassQueryEngineError;
+
+///
+/// A query engine error, located in the query when possible.
+///
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct QueryEngineError {
+    /// The kind of the error
+    pub kind: ZkPassQueryEngineError,
+
+    /// The JSON pointer (RFC 6901) of the offending element in the query, e.g. "/0/assign/result".
+    /// `None` when the error is not caused by the query, e.g. malformed user data.
+    pub location: Option<String>,
+
+    /// The offending token, e.g. a statement keyword, an operator symbol or a dvar path
+    pub token: Option<String>,
+
+    /// A human readable description of the error
+    pub message: String,
+}
+
+impl QueryEngineError {
+    pub fn new(kind: ZkPassQueryEngineError, message: impl Into<String>) -> Self {
+        QueryEngineError {
+            kind,
+            location: None,
+            token: None,
+            message: message.into(),
+        }
+    }
+
+    // Sets the location of the error in the query.
+    pub fn at(mut self, location: &str) -> Self {
+        self.location = Some(location.to_string());
+        self
+    }
+
+    // Sets the offending token.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    // Prefixes the location with the reference tokens of an enclosing element.
+    // The evaluator calls it while the error propagates up the query,
+    // so the location is only built when an error actually happens.
+    #[cfg(feature = "preview")]
+    pub(crate) fn within(mut self, tokens: &[&str]) -> Self {
+        let prefix = tokens.iter().fold(String::new(), |parent, token| pointer(&parent, token));
+        self.location = Some(prefix + self.location.as_deref().unwrap_or(""));
+        self
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+impl From<ZkPassQueryEngineError> for QueryEngineError {
+    fn from(kind: ZkPassQueryEngineError) -> Self {
+        let message = kind.to_string();
+        QueryEngineError::new(kind, message)
+    }
+}
+
+impl fmt::Display for QueryEngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.kind)?;
+        if let Some(location) = &self.location {
+            write!(f, " at '{}'", location)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for QueryEngineError {}
+
+impl fmt::Display for ZkPassQueryEngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ZkPassQueryEngineError::UnhandledPanicError => "unhandled panic in the query engine",
+            ZkPassQueryEngineError::UnexpectedValueError => "unexpected value",
+            ZkPassQueryEngineError::UnexpectedOperatorError => "unexpected operator",
+            ZkPassQueryEngineError::QueryParsingError => "the query is malformed",
+            ZkPassQueryEngineError::DataVariableResolutionError => "the dvar cannot be resolved in the user data",
+            ZkPassQueryEngineError::ProofGenerationError => "the proof cannot be generated",
+            ZkPassQueryEngineError::ProofSerializationError => "the proof cannot be serialized",
+            ZkPassQueryEngineError::UnknownStmtKeywordParsingError => "unknown statement keyword",
+            ZkPassQueryEngineError::UnexpectedStmtError => "unexpected statement",
+            ZkPassQueryEngineError::UserDataParsingError => "the user data is malformed",
+            ZkPassQueryEngineError::LocalVarParsingError => "the lvar is not assigned",
+            ZkPassQueryEngineError::DataVarParsingError => "the dvar path is malformed",
+            ZkPassQueryEngineError::DvrNotAnArrayError => "the query must be an array of statements",
+            ZkPassQueryEngineError::DataVarNameNotStartingWithAlphabetError => "the dvar path must start with an alphabet",
+            ZkPassQueryEngineError::ExpectingOperandsInArrayParsingError => "the operands must be an array",
+            ZkPassQueryEngineError::ExpectingFirstOperandParsingError => "missing the first operand",
+            ZkPassQueryEngineError::ExpectingSecondOperandParsingError => "missing the second operand",
+            ZkPassQueryEngineError::ExpectingThirdOperandParsingError => "missing or invalid third operand",
+            ZkPassQueryEngineError::OutputStatementExpectingOperandInObjectParsingError =>
+                "the output statement expects an object operand",
+            ZkPassQueryEngineError::OutputStatementExpectingOneOperandParsingError =>
+                "the output statement expects one operand",
+            ZkPassQueryEngineError::OutputStatementParsingError => "the output statement is malformed",
+            ZkPassQueryEngineError::AssignmentStatementExpectingOperandInObjectParsingError =>
+                "the assign statement expects an object operand",
+            ZkPassQueryEngineError::AssignmentStatementExpectingOneOperandParsingError =>
+                "the assign statement expects one operand",
+            ZkPassQueryEngineError::AssignmentStatementParsingError => "the assign statement is malformed",
+            ZkPassQueryEngineError::IfStatementExpectingOperandInObjectParsingError =>
+                "the if statement expects an object operand",
+            ZkPassQueryEngineError::IfStatementExpectingThreeOperandOrLessParsingError =>
+                "the if statement expects three operands or less",
+            ZkPassQueryEngineError::IfStatementUnknownKeywordParsingError => "unknown if statement keyword",
+            ZkPassQueryEngineError::IfStatementMissingConditionParsingError => "the if statement is missing the condition",
+            ZkPassQueryEngineError::IfStatementMissingThenBlockParsingError => "the if statement is missing the then block",
+            ZkPassQueryEngineError::UnexpectedOperatorParsingError => "unexpected operator",
+            ZkPassQueryEngineError::ArrayComparisonNotSupportedError => "comparing two arrays is not supported",
+            ZkPassQueryEngineError::CyclesLimitExceededError => "the cycles limit of the zkvm is exceeded",
+            ZkPassQueryEngineError::EnvironmentError => "the zkvm environment cannot be created",
+            ZkPassQueryEngineError::SerializationError => "the input cannot be serialized",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::ArithmeticOverflowError => "the arithmetic operation overflows",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::DivisionByZeroError => "division by zero",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::ReferenceTimeError => "the reference time is out of range",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::ItemVarParsingError => "the ivar is not inside an array operator",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::ConstStatementParsingError => "the const statement is malformed",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::SwitchStatementParsingError => "the switch statement is malformed",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::DefineStatementParsingError => "the define statement is malformed",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::SaltError => "the salt is missing or malformed",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::VerifierIdentityError => "the verifier identity is missing",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::MaskingError => "only a string can be masked",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::TextStatementParsingError => "the text statement is malformed",
+            #[cfg(feature = "preview")]
+            ZkPassQueryEngineError::InexactNumberError => "the number cannot be represented exactly",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+// Appends a reference token to a JSON pointer, escaping '~' and '/' as required by RFC 6901.
+#[cfg(feature = "preview")]
+pub(crate) fn pointer(parent: &str, token: &str) -> String {
+    format!("{}/{}", parent, token.replace('~', "~0").replace('/', "~1"))
+}
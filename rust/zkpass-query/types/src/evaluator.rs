@@ -0,0 +1,1221 @@
+//
+// Native (non-proving) evaluator of the zkPass query language.
+//
+// The evaluator runs a DVR query against the tagged user data outside of the zkvm,
+// and produces the same output JSON that `OutputReader::from_json` consumes.
+// It lets DVR authors iterate on a query locally, and lets the data holder preview
+// the query result before consenting to the proof generation.
+//
+// It is built with the `preview` feature, as it also runs the statements and operators that the
+// engine of the proof method does not run yet; `parse_query_for_engine` rejects those.
+//
+use jmespath::ast::Ast;
+use jmespath::Variable;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::{Definition, Expression, Literal, LiteralSet, Operator, Quantifier, Query, Statement};
+use crate::commitment::{self, SaltError};
+use crate::decimal::inexact_numbers;
+use crate::error::QueryEngineError;
+use crate::pseudonym::{self, DvrVerifyingKey};
+use crate::regex::Regex;
+use crate::similarity;
+use crate::text::TextOptions;
+use crate::parser::{keyword, parse_query};
+use crate::{Decimal, Entry, LocalDate, LocalDateTime, OutputTable, ReferenceTime, SymbolTable, Val, ZkPassQueryEngineError};
+
+///
+/// The input of the evaluator.
+/// It holds the user data and the query of the `ProofMethodInput` of the proof method, together with
+/// the inputs of the operators that the proving engine does not run yet, so it is a distinct type.
+/// `PreviewInput::from_proof_method_input` converts a proof method input explicitly.
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreviewInput {
+    /// The user data JSON documents, keyed by their tag.
+    /// A single user data may use the empty tag "", in which case
+    /// its fields are referenced directly by the `dvar` paths.
+    pub user_data: HashMap<String, String>,
+
+    /// The zkPass Query script, which is encoded in JSON string format
+    pub query: String,
+
+    /// The reference time of the date operators, supplied by the verifier.
+    /// It is committed to the output, so the verifier can check which "today" the query used.
+    /// Without it, the date operators use the local date of the host.
+    #[serde(default)]
+    pub reference_time: Option<ReferenceTime>,
+
+    /// The hex salts of the `commit` operators, keyed by the salt name of the query.
+    /// They are supplied privately by the data holder, and only the commitments are output.
+    /// A salt must not be reused for another proof, or the commitments can be linked.
+    #[serde(default)]
+    pub salts: HashMap<String, String>,
+
+    /// The public key that the DVR signature was verified with.
+    /// The `pseudonym` operator derives the pseudonyms for the verifier identity of this key, and the
+    /// identity is committed to the output, so the verifier can check that the pseudonyms are scoped to it.
+    #[serde(default)]
+    pub dvr_verifying_key: Option<DvrVerifyingKey>,
+}
+
+impl PreviewInput {
+    ///
+    /// Creates the input of the given user data and query, without a reference time, salts or verifying key.
+    ///
+    pub fn new(user_data: HashMap<String, String>, query: String) -> Self {
+        PreviewInput {
+            user_data,
+            query,
+            reference_time: None,
+            salts: HashMap::new(),
+            dvr_verifying_key: None,
+        }
+    }
+
+    ///
+    /// Converts the `ProofMethodInput` of the proof method through its serialized fields.
+    /// A field that the evaluator does not know is rejected instead of being ignored,
+    /// so the preview never evaluates a different input than the proof method would.
+    ///
+    pub fn from_proof_method_input<T: Serialize>(input: &T) -> Result<Self, QueryEngineError> {
+        serde_json::to_value(input)
+            .and_then(serde_json::from_value)
+            .map_err(|err| QueryEngineError::new(ZkPassQueryEngineError::SerializationError, err.to_string()))
+    }
+}
+
+///
+/// Evaluates the query of the `PreviewInput` against its user data without generating a proof.
+/// Returns the output JSON of the query, which is the output that the zkvm proof method commits
+/// when the query is accepted by `parse_query_for_engine`.
+/// An error is located in the query whenever it is caused by the query.
+///
+pub fn evaluate_query(input: &PreviewInput) -> Result<String, QueryEngineError> {
+    let query = parse_query(&input.query).map_err(|errors| {
+        errors
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| ZkPassQueryEngineError::QueryParsingError.into())
+    })?;
+
+    evaluate_parsed_query(&query, merge_user_data(&input.user_data)?, input)
+}
+
+// Evaluates an already parsed query against the merged user data.
+fn evaluate_parsed_query(
+    query: &Query,
+    user_data: Value,
+    input: &PreviewInput
+) -> Result<String, QueryEngineError> {
+    let reference_time = input.reference_time;
+    let now = match reference_time {
+        Some(reference_time) => reference_time.now(),
+        None => Some(LocalDateTime::now()),
+    };
+    let now = now.ok_or_else(|| {
+        QueryEngineError::new(
+            ZkPassQueryEngineError::ReferenceTimeError,
+            format!("the reference time {:?} is out of range", reference_time)
+        )
+    })?;
+
+    let verifier_id = input.dvr_verifying_key.as_ref().map(pseudonym::verifier_id);
+    let mut evaluator = QueryEvaluator::new(user_data, now, input.salts.clone(), verifier_id.clone());
+    // The reference time is the first output, so the verifier reads it at a fixed name
+    if let Some(reference_time) = reference_time.and_then(|reference_time| reference_time.to_rfc3339()) {
+        evaluator.outputs.set(Entry {
+            key: ReferenceTime::OUTPUT_NAME.to_string(),
+            val: Val::Str(reference_time),
+        });
+    }
+    if let Some(verifier_id) = verifier_id {
+        evaluator.outputs.set(Entry {
+            key: pseudonym::VERIFIER_ID_OUTPUT_NAME.to_string(),
+            val: Val::Str(verifier_id),
+        });
+    }
+    evaluator.execute_block(&query.statements)?;
+
+    Ok(evaluator.outputs.to_json())
+}
+
+// A single untagged user data is the root of the dvar paths,
+// otherwise each user data is placed under its tag.
+fn merge_user_data(user_data: &HashMap<String, String>) -> Result<Value, QueryEngineError> {
+    // A number that would be rounded is rejected, so a dvar is always the number of the user data
+    let parse = |tag: &str, data: &String| {
+        let value = serde_json::from_str::<Value>(data).map_err(|err| {
+            QueryEngineError::new(ZkPassQueryEngineError::UserDataParsingError, err.to_string()).with_token(tag)
+        })?;
+        match inexact_numbers(data).first() {
+            Some((location, literal)) => Err(
+                QueryEngineError::new(
+                    ZkPassQueryEngineError::InexactNumberError,
+                    format!("the number {} at '{}' of the user data cannot be represented exactly", literal, location)
+                ).with_token(tag)
+            ),
+            None => Ok(value),
+        }
+    };
+
+    if user_data.len() == 1 {
+        if let Some(data) = user_data.get("") {
+            return parse("", data);
+        }
+    }
+
+    let mut root = Map::new();
+    for (tag, data) in user_data {
+        if tag.is_empty() {
+            return Err(
+                QueryEngineError::new(
+                    ZkPassQueryEngineError::UserDataParsingError,
+                    "the empty tag is only allowed for a single user data"
+                )
+            );
+        }
+        root.insert(tag.clone(), parse(tag, data)?);
+    }
+    Ok(Value::Object(root))
+}
+
+// The errors raised while evaluating an element of the query are located relative to that element,
+// and each enclosing element prefixes the location with its own reference tokens.
+struct QueryEvaluator {
+    user_data: Value,
+    local_vars: SymbolTable,
+    outputs: OutputTable,
+    now: LocalDateTime,
+    // The current elements of the enclosing array operators, the innermost last
+    items: Vec<Val>,
+    constants: HashMap<String, LiteralSet>,
+    definitions: HashMap<String, Rc<Definition>>,
+    // The arguments of the enclosing definitions, the innermost last
+    params: Vec<SymbolTable>,
+    salts: HashMap<String, String>,
+    verifier_id: Option<String>,
+    text: TextOptions,
+}
+
+impl QueryEvaluator {
+    fn new(
+        user_data: Value,
+        now: LocalDateTime,
+        salts: HashMap<String, String>,
+        verifier_id: Option<String>
+    ) -> Self {
+        QueryEvaluator {
+            user_data,
+            now,
+            local_vars: SymbolTable::new(),
+            outputs: OutputTable::new(),
+            items: Vec::new(),
+            constants: HashMap::new(),
+            definitions: HashMap::new(),
+            params: Vec::new(),
+            salts,
+            verifier_id,
+            text: TextOptions::default(),
+        }
+    }
+
+    fn execute_block(&mut self, statements: &[Statement]) -> Result<(), QueryEngineError> {
+        for (index, statement) in statements.iter().enumerate() {
+            self.execute_statement(statement).map_err(|err| err.within(&[&index.to_string()]))?;
+        }
+        Ok(())
+    }
+
+    fn execute_statement(&mut self, statement: &Statement) -> Result<(), QueryEngineError> {
+        match statement {
+            Statement::Assign { name, expression } => {
+                let val = self.evaluate_expression(expression).map_err(|err| err.within(&[keyword::ASSIGN, name]))?;
+                self.local_vars.set(Entry { key: name.clone(), val });
+            }
+            Statement::Output { name, expression } => {
+                let val = self.evaluate_expression(expression).map_err(|err| err.within(&[keyword::OUTPUT, name]))?;
+                self.outputs.set(Entry { key: name.clone(), val });
+            }
+            Statement::Const { name, values } => {
+                self.constants.insert(name.clone(), values.clone());
+            }
+            Statement::Text(text) => {
+                self.text = *text;
+            }
+            Statement::Define { definitions } => {
+                for definition in definitions {
+                    self.definitions.insert(definition.name.clone(), Rc::new(definition.clone()));
+                }
+            }
+            Statement::If { condition, then_block, else_block } => {
+                let condition = self
+                    .evaluate_condition(condition)
+                    .map_err(|err| err.within(&[keyword::IF, keyword::CONDITION]))?;
+                let (block_keyword, block) = if condition {
+                    (keyword::THEN, then_block)
+                } else {
+                    (keyword::ELSE, else_block)
+                };
+                self.execute_block(block).map_err(|err| err.within(&[keyword::IF, block_keyword]))?;
+            }
+            Statement::Switch { cases, default_block } => {
+                for (index, case) in cases.iter().enumerate() {
+                    let index = index.to_string();
+                    if self.evaluate_condition(&case.condition).map_err(|err| {
+                        err.within(&[keyword::SWITCH, keyword::CASES, &index, keyword::CONDITION])
+                    })? {
+                        return self
+                            .execute_block(&case.block)
+                            .map_err(|err| err.within(&[keyword::SWITCH, keyword::CASES, &index, keyword::THEN]));
+                    }
+                }
+                self.execute_block(default_block).map_err(|err| err.within(&[keyword::SWITCH, keyword::DEFAULT]))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate_condition(&mut self, condition: &Expression) -> Result<bool, QueryEngineError> {
+        match self.evaluate_expression(condition)? {
+            Val::Bool(b) => Ok(b),
+            val => Err(unexpected_value("the condition must be a boolean", &val)),
+        }
+    }
+
+    fn evaluate_expression(&mut self, expression: &Expression) -> Result<Val, QueryEngineError> {
+        match expression {
+            Expression::Str(s) => Ok(Val::Str(s.clone())),
+            Expression::Int(i) => Ok(Val::Int(*i)),
+            Expression::Decimal(d) => Ok(Val::Decimal(*d)),
+            Expression::Bool(b) => Ok(Val::Bool(*b)),
+            Expression::Array(items) => Ok(Val::Array(self.evaluate_expressions(items)?)),
+            Expression::DataVar(path) => {
+                self.resolve_data_var(path).map_err(|err| err.with_token(path).within(&[keyword::DVAR]))
+            }
+            Expression::DataVarOrDefault { path, default } => {
+                match self.find_data_var(path).map_err(|err| err.with_token(path).within(&[keyword::DVAR]))? {
+                    Some(val) => Ok(val),
+                    None => self.evaluate_expression(default).map_err(|err| err.within(&[keyword::DEFAULT])),
+                }
+            }
+            // A local variable is unassigned when its assign statement is in a branch that is not taken.
+            Expression::LocalVar(name) => {
+                self.local_vars.find(name).cloned().ok_or_else(|| {
+                    QueryEngineError::new(
+                        ZkPassQueryEngineError::LocalVarParsingError,
+                        format!("the lvar '{}' is not assigned", name)
+                    )
+                        .with_token(name)
+                        .within(&[keyword::LVAR])
+                })
+            }
+            Expression::ItemVar(path) => {
+                self.resolve_item_var(path).map_err(|err| err.with_token(path).within(&[keyword::IVAR]))
+            }
+            Expression::Constant(name) => {
+                self.find_constant(name).map(LiteralSet::to_val).map_err(|err| err.within(&[keyword::CONST]))
+            }
+            Expression::Set(set) => Ok(set.to_val()),
+            Expression::Reference { name, args } => {
+                self.evaluate_reference(name, args).map_err(|err| err.within(&[keyword::REF]))
+            }
+            Expression::Param(name) => {
+                self.params.last().and_then(|params| params.find(name)).cloned().ok_or_else(|| {
+                    QueryEngineError::new(
+                        ZkPassQueryEngineError::DefineStatementParsingError,
+                        format!("the param '{}' is not bound", name)
+                    )
+                        .with_token(name)
+                        .within(&[keyword::PARAM])
+                })
+            }
+            Expression::Comparison { operator, left, right, quantifier } => {
+                self.evaluate_comparison(*operator, left, right, *quantifier).map_err(|err| at_operator(err, *operator))
+            }
+            Expression::Operation { operator, operands } if operator.is_presence() => {
+                self.evaluate_presence(*operator, &operands[0]).map_err(|err| at_operator(err, *operator))
+            }
+            Expression::Operation { operator, operands } if operator.is_membership() => {
+                self.evaluate_membership(*operator, operands).map_err(|err| at_operator(err, *operator))
+            }
+            Expression::Operation { operator, operands } if operator.is_array() => {
+                self.evaluate_array_operation(*operator, operands).map_err(|err| at_operator(err, *operator))
+            }
+            Expression::Operation { operator, operands } => {
+                let result = self.evaluate_expressions(operands).and_then(|operands| {
+                    match operator {
+                        Operator::And | Operator::Or => evaluate_logical(*operator, &operands),
+                        _ if operator.is_date() => evaluate_date(*operator, &operands, &self.now),
+                        _ if operator.is_arithmetic() => evaluate_arithmetic(*operator, &operands),
+                        _ if operator.is_set() => evaluate_set(*operator, &operands, &self.text),
+                        Operator::Length => match &operands[0] {
+                            Val::Array(items) => Ok(Val::Int(items.len() as i64)),
+                            Val::Str(s) => Ok(Val::Int(s.chars().count() as i64)),
+                            operand => Err(unexpected_value("the operand must be an array or a string", operand)),
+                        },
+                        Operator::Substring => evaluate_substring(&operands),
+                        Operator::Bucket => evaluate_bucket(&operands),
+                        Operator::RoundDown => evaluate_round_down(&operands),
+                        Operator::Commit => self.evaluate_commit(&operands),
+                        Operator::BlindedIn => evaluate_blinded_in(&operands),
+                        Operator::Pseudonym => self.evaluate_pseudonym(&operands),
+                        Operator::Mask => evaluate_mask(&operands),
+                        Operator::Similar | Operator::Similarity => evaluate_similarity(*operator, &operands, &self.text),
+                        _ => Err(QueryEngineError::new(ZkPassQueryEngineError::UnexpectedOperatorError, "unexpected operator")),
+                    }
+                });
+                result.map_err(|err| at_operator(err, *operator))
+            }
+        }
+    }
+
+    // All operands are evaluated, so that an invalid operand is always reported
+    // regardless of the values of the operands before it.
+    fn evaluate_expressions(&mut self, expressions: &[Expression]) -> Result<Vec<Val>, QueryEngineError> {
+        expressions
+            .iter()
+            .enumerate()
+            .map(|(index, expression)| {
+                self.evaluate_expression(expression).map_err(|err| err.within(&[&index.to_string()]))
+            })
+            .collect()
+    }
+
+    // Comparing an array with a scalar applies the comparison to each element of the array,
+    // and the quantifier decides whether all elements or any element must match.
+    fn evaluate_comparison(
+        &mut self,
+        operator: Operator,
+        left: &Expression,
+        right: &Expression,
+        quantifier: Quantifier
+    ) -> Result<Val, QueryEngineError> {
+        let lhs = self.evaluate_expression(left).map_err(|err| err.within(&["0"]))?;
+        let rhs = self.evaluate_expression(right).map_err(|err| err.within(&["1"]))?;
+        let results = match (&lhs, &rhs) {
+            // Two arrays are equal when they have equal elements in the same order
+            (Val::Array(_), Val::Array(_)) if operator == Operator::Eq => vec![equal(&lhs, &rhs, &self.text)],
+            (Val::Array(_), Val::Array(_)) => {
+                return Err(
+                    QueryEngineError::new(
+                        ZkPassQueryEngineError::ArrayComparisonNotSupportedError,
+                        format!("comparing two arrays is only supported by '{}'", Operator::Eq.symbol())
+                    )
+                );
+            }
+            (Val::Array(items), _) => items
+                .iter()
+                .map(|item| compare(operator, item, &rhs, &self.text))
+                .collect::<Result<Vec<bool>, _>>()?,
+            (_, Val::Array(items)) => items
+                .iter()
+                .map(|item| compare(operator, &lhs, item, &self.text))
+                .collect::<Result<Vec<bool>, _>>()?,
+            _ => vec![compare(operator, &lhs, &rhs, &self.text)?],
+        };
+
+        let result = match quantifier {
+            Quantifier::All => results.iter().all(|r| *r),
+            Quantifier::Any => results.iter().any(|r| *r),
+        };
+        Ok(Val::Bool(result))
+    }
+
+    // Evaluates the predicate or the mapping in the second operand for each element of the array
+    // in the first operand. Without a second operand, count, sum, min and max take the elements themselves.
+    fn evaluate_array_operation(&mut self, operator: Operator, operands: &[Expression]) -> Result<Val, QueryEngineError> {
+        let items = match self.evaluate_expression(&operands[0]).map_err(|err| err.within(&["0"]))? {
+            Val::Array(items) => items,
+            val => {
+                return Err(unexpected_value("the first operand must be an array", &val).within(&["0"]));
+            }
+        };
+        let values = match operands.get(1) {
+            Some(expression) => items
+                .iter()
+                .map(|item| {
+                    self.items.push(item.clone());
+                    let result = self.evaluate_expression(expression);
+                    self.items.pop();
+                    result
+                })
+                .collect::<Result<Vec<Val>, _>>()
+                .map_err(|err| err.within(&["1"]))?,
+            None => items.clone(),
+        };
+        let predicate = || {
+            values
+                .iter()
+                .map(|value| match value {
+                    Val::Bool(b) => Ok(*b),
+                    _ => Err(unexpected_value("the predicate must be a boolean", value).within(&["1"])),
+                })
+                .collect::<Result<Vec<bool>, _>>()
+        };
+
+        match operator {
+            Operator::Filter => {
+                let matches = predicate()?;
+                let items = items
+                    .into_iter()
+                    .zip(matches)
+                    .filter(|(_, matched)| *matched)
+                    .map(|(item, _)| item)
+                    .collect();
+                Ok(Val::Array(items))
+            }
+            Operator::Map => Ok(Val::Array(values)),
+            Operator::Count if operands.len() == 1 => Ok(Val::Int(items.len() as i64)),
+            Operator::Count => Ok(Val::Int(predicate()?.iter().filter(|matched| **matched).count() as i64)),
+            Operator::Sum => evaluate_arithmetic(Operator::Add, &values),
+            Operator::Min | Operator::Max => {
+                let wanted = if operator == Operator::Min { Operator::Lt } else { Operator::Gt };
+                let (first, rest) = values
+                    .split_first()
+                    .ok_or_else(|| QueryEngineError::new(ZkPassQueryEngineError::UnexpectedValueError, "the array is empty"))?;
+                rest.iter().try_fold(first.clone(), |best, value| {
+                    Ok(if compare(wanted, value, &best, &self.text)? { value.clone() } else { best })
+                })
+            }
+            Operator::AnyOf => Ok(Val::Bool(predicate()?.iter().any(|matched| *matched))),
+            Operator::AllOf => Ok(Val::Bool(predicate()?.iter().all(|matched| *matched))),
+            _ => Ok(Val::Bool(!predicate()?.iter().any(|matched| *matched))),
+        }
+    }
+
+    // A literal list or a const list is a sorted set, which is binary searched,
+    // and any other array is searched element by element.
+    fn evaluate_membership(&mut self, operator: Operator, operands: &[Expression]) -> Result<Val, QueryEngineError> {
+        let val = self.evaluate_expression(&operands[0]).map_err(|err| err.within(&["0"]))?;
+        let set = match &operands[1] {
+            Expression::Set(set) => Some(set),
+            Expression::Constant(name) => {
+                Some(self.find_constant(name).map_err(|err| err.within(&["1", keyword::CONST]))?)
+            }
+            _ => None,
+        };
+        let found = match set {
+            Some(set) => {
+                let literal = match Literal::from_val(&val) {
+                    // The strings of the set are normalized by the parser
+                    Some(Literal::Str(s)) => Literal::Str(self.text.normalize(&s).into_owned()),
+                    Some(literal) => literal,
+                    None => {
+                        return Err(
+                            unexpected_value("the value must be a string, a number or a boolean", &val).within(&["0"])
+                        );
+                    }
+                };
+                set.contains(&literal)
+            }
+            None => match self.evaluate_expression(&operands[1]).map_err(|err| err.within(&["1"]))? {
+                Val::Array(items) => items.iter().any(|item| equal(item, &val, &self.text)),
+                list => {
+                    return Err(unexpected_value("the list must be an array", &list).within(&["1"]));
+                }
+            },
+        };
+        Ok(Val::Bool(found == (operator == Operator::In)))
+    }
+
+    // Evaluates the body of a definition with its parameters bound to the arguments.
+    // An error in the body is located at the reference, and its message tells where it is in the body.
+    fn evaluate_reference(&mut self, name: &str, args: &[Expression]) -> Result<Val, QueryEngineError> {
+        let definition = self.definitions.get(name).cloned().ok_or_else(|| {
+            QueryEngineError::new(
+                ZkPassQueryEngineError::DefineStatementParsingError,
+                format!("the definition '{}' is not defined", name)
+            )
+                .with_token(name)
+        })?;
+        if definition.params.len() != args.len() {
+            return Err(
+                QueryEngineError::new(
+                    ZkPassQueryEngineError::DefineStatementParsingError,
+                    format!("the definition '{}' expects {} arguments, found {}", name, definition.params.len(), args.len())
+                ).with_token(name)
+            );
+        }
+
+        let mut params = SymbolTable::new();
+        for (index, (param, arg)) in definition.params.iter().zip(args).enumerate() {
+            let val = self.evaluate_expression(arg).map_err(|err| err.within(&[&(index + 1).to_string()]))?;
+            params.add(Entry { key: param.clone(), val });
+        }
+        self.params.push(params);
+        let result = self.evaluate_expression(&definition.body);
+        self.params.pop();
+
+        result.map_err(|err| {
+            let message = format!(
+                "the definition '{}' fails at '{}': {}",
+                name,
+                err.location.as_deref().unwrap_or(""),
+                err.message
+            );
+            QueryEngineError { location: None, message, ..err }
+        })
+    }
+
+    // Evaluates `[value, salt name]` into the commitment of the value with the salt of the holder.
+    fn evaluate_commit(&self, operands: &[Val]) -> Result<Val, QueryEngineError> {
+        let name = match &operands[1] {
+            Val::Str(name) => name,
+            operand => {
+                return Err(unexpected_value("the salt name must be a string", operand));
+            }
+        };
+        let salt = self.salts.get(name).ok_or_else(|| {
+            QueryEngineError::new(ZkPassQueryEngineError::SaltError, format!("the salt '{}' is not supplied", name))
+                .with_token(name)
+        })?;
+        let salt = commitment::parse_salt(salt).map_err(|err| {
+            let message = match err {
+                SaltError::MalformedSaltError => format!("the salt '{}' is not a hex string", name),
+                SaltError::ShortSaltError => {
+                    format!("the salt '{}' is shorter than {} bytes", name, commitment::MIN_SALT_LENGTH)
+                }
+            };
+            QueryEngineError::new(ZkPassQueryEngineError::SaltError, message).with_token(name)
+        })?;
+        Ok(Val::Str(commitment::commit(&salt, &operands[0])))
+    }
+
+    // Evaluates `[value]` or `[value, context]` into the pseudonym of the value for the verifier.
+    fn evaluate_pseudonym(&self, operands: &[Val]) -> Result<Val, QueryEngineError> {
+        let verifier_id = self.verifier_id.as_deref().ok_or_else(|| {
+            QueryEngineError::new(
+                ZkPassQueryEngineError::VerifierIdentityError,
+                "the DVR verifying key is not supplied"
+            )
+        })?;
+        let context = match operands.get(1) {
+            Some(Val::Str(context)) => context,
+            Some(operand) => {
+                return Err(unexpected_value("the context must be a string", operand));
+            }
+            None => "",
+        };
+        if operands[0] == Val::Null {
+            return Err(unexpected_value("the value must not be null", &operands[0]));
+        }
+        Ok(Val::Str(pseudonym::pseudonym(verifier_id, context, &operands[0])))
+    }
+
+    fn find_constant(&self, name: &str) -> Result<&LiteralSet, QueryEngineError> {
+        self.constants.get(name).ok_or_else(|| {
+            QueryEngineError::new(
+                ZkPassQueryEngineError::ConstStatementParsingError,
+                format!("the const '{}' is not declared", name)
+            )
+                .with_token(name)
+        })
+    }
+
+    fn resolve_item_var(&self, path: &str) -> Result<Val, QueryEngineError> {
+        let item = self.items.last().ok_or_else(|| {
+            QueryEngineError::new(ZkPassQueryEngineError::ItemVarParsingError, "the ivar is not inside an array operator")
+        })?;
+        let val = if path == "@" { Some(item) } else { item.find_path(path) };
+        val.cloned().ok_or_else(|| {
+            QueryEngineError::new(
+                ZkPassQueryEngineError::DataVariableResolutionError,
+                format!("the ivar '{}' is not found in the element", path)
+            )
+        })
+    }
+
+    // Evaluates `exists` or `is_null` of a dvar or an ivar.
+    fn evaluate_presence(&self, operator: Operator, operand: &Expression) -> Result<Val, QueryEngineError> {
+        let presence = match operand {
+            Expression::DataVar(path) => {
+                self.data_var_presence(path).map_err(|err| err.with_token(path).within(&["0", keyword::DVAR]))?
+            }
+            Expression::ItemVar(path) => self.item_var_presence(path).map_err(|err| err.within(&["0", keyword::IVAR]))?,
+            _ => {
+                return Err(
+                    QueryEngineError::new(
+                        ZkPassQueryEngineError::UnexpectedValueError,
+                        "the operand must be a dvar or an ivar"
+                    ).within(&["0"])
+                );
+            }
+        };
+        Ok(
+            Val::Bool(match operator {
+                Operator::Exists => presence != Presence::Missing,
+                _ => presence != Presence::Value,
+            })
+        )
+    }
+
+    fn data_var_presence(&self, path: &str) -> Result<Presence, QueryEngineError> {
+        let expression = compile_data_var(path)?;
+        let result = expression.search(&self.user_data).map_err(|err| {
+            QueryEngineError::new(ZkPassQueryEngineError::DataVariableResolutionError, err.to_string())
+        })?;
+        if !result.is_null() {
+            Ok(Presence::Value)
+        } else if is_present(path, expression.as_ast(), &self.user_data) {
+            Ok(Presence::Null)
+        } else {
+            Ok(Presence::Missing)
+        }
+    }
+
+    fn item_var_presence(&self, path: &str) -> Result<Presence, QueryEngineError> {
+        let item = self.items.last().ok_or_else(|| {
+            QueryEngineError::new(ZkPassQueryEngineError::ItemVarParsingError, "the ivar is not inside an array operator")
+        })?;
+        let val = if path == "@" { Some(item) } else { item.find_path(path) };
+        Ok(match val {
+            Some(Val::Null) => Presence::Null,
+            Some(_) => Presence::Value,
+            None => Presence::Missing,
+        })
+    }
+
+    fn resolve_data_var(&self, path: &str) -> Result<Val, QueryEngineError> {
+        if let Some(val) = self.find_data_var(path)? {
+            return Ok(val);
+        }
+        let expression = compile_data_var(path)?;
+        let message = match missing_prefix(path, expression.as_ast(), &self.user_data) {
+            Some(prefix) => format!("the dvar '{}' is not found in the user data: '{}' resolves to nothing", path, prefix),
+            None => format!("the dvar '{}' is not found in the user data", path),
+        };
+        Err(QueryEngineError::new(ZkPassQueryEngineError::DataVariableResolutionError, message))
+    }
+
+    // Finds the value of a dvar in the user data, or `None` when the path is missing.
+    // jmespath resolves a missing path to null too, so a null is only `Val::Null` when the path is present.
+    fn find_data_var(&self, path: &str) -> Result<Option<Val>, QueryEngineError> {
+        let expression = compile_data_var(path)?;
+        let result = expression.search(&self.user_data).map_err(|err| {
+            QueryEngineError::new(ZkPassQueryEngineError::DataVariableResolutionError, err.to_string())
+        })?;
+        if result.is_null() {
+            return Ok(is_present(path, expression.as_ast(), &self.user_data).then_some(Val::Null));
+        }
+        val_from_variable(&result).map(Some)
+    }
+}
+
+// Locates an error raised by an operator at the operator, unless an operand already located it.
+fn at_operator(err: QueryEngineError, operator: Operator) -> QueryEngineError {
+    let err = if err.token.is_none() { err.with_token(operator.symbol()) } else { err };
+    err.within(&[operator.symbol()])
+}
+
+fn unexpected_value(message: &str, val: &Val) -> QueryEngineError {
+    QueryEngineError::new(ZkPassQueryEngineError::UnexpectedValueError, format!("{}, found {}", message, val.to_json()))
+}
+
+fn evaluate_logical(operator: Operator, operands: &[Val]) -> Result<Val, QueryEngineError> {
+    let mut result = operator == Operator::And;
+    for operand in operands {
+        let value = match operand {
+            Val::Bool(b) => *b,
+            _ => {
+                return Err(unexpected_value("the operands must be booleans", operand));
+            }
+        };
+        result = if operator == Operator::And { result && value } else { result || value };
+    }
+    Ok(Val::Bool(result))
+}
+
+// Folds the numeric operands from left to right, e.g. `{ "-": [a, b] }` is `a - b`.
+// The operands are integers, unless any of them is a decimal, in which case they are all taken as decimals.
+// An overflow, or a division by zero, is an error rather than a wrapped or saturated result.
+fn evaluate_arithmetic(operator: Operator, operands: &[Val]) -> Result<Val, QueryEngineError> {
+    if operands.iter().any(|operand| matches!(operand, Val::Decimal(_))) {
+        let decimals = operands
+            .iter()
+            .map(|operand| to_decimal(operand).ok_or_else(|| unexpected_value("the operands must be numbers", operand)))
+            .collect::<Result<Vec<Decimal>, _>>()?;
+        let result = fold_arithmetic(operator, &decimals, Decimal::from_i64(0), |lhs, rhs| match operator {
+            Operator::Add => lhs.checked_add(rhs),
+            Operator::Sub => lhs.checked_sub(rhs),
+            Operator::Mul => lhs.checked_mul(rhs),
+            Operator::Div => lhs.checked_div(rhs),
+            _ => lhs.checked_rem(rhs),
+        })?;
+        return Ok(Val::Decimal(result));
+    }
+
+    let integers = operands
+        .iter()
+        .map(|operand| match operand {
+            Val::Int(i) => Ok(*i),
+            _ => Err(unexpected_value("the operands must be numbers", operand)),
+        })
+        .collect::<Result<Vec<i64>, _>>()?;
+    let result = fold_arithmetic(operator, &integers, 0, |lhs, rhs| match operator {
+        Operator::Add => lhs.checked_add(*rhs),
+        Operator::Sub => lhs.checked_sub(*rhs),
+        Operator::Mul => lhs.checked_mul(*rhs),
+        Operator::Div => lhs.checked_div(*rhs),
+        _ => lhs.checked_rem(*rhs),
+    })?;
+    Ok(Val::Int(result))
+}
+
+fn fold_arithmetic<T: Copy + PartialEq + std::fmt::Display>(
+    operator: Operator,
+    operands: &[T],
+    zero: T,
+    apply: impl Fn(&T, &T) -> Option<T>
+) -> Result<T, QueryEngineError> {
+    let (first, rest) = operands.split_first().unwrap_or((&zero, &[]));
+    rest.iter().try_fold(*first, |lhs, rhs| {
+        if matches!(operator, Operator::Div | Operator::Rem) && *rhs == zero {
+            return Err(
+                QueryEngineError::new(
+                    ZkPassQueryEngineError::DivisionByZeroError,
+                    format!("{} {} 0 divides by zero", lhs, operator.symbol())
+                )
+            );
+        }
+        apply(&lhs, rhs).ok_or_else(|| {
+            QueryEngineError::new(
+                ZkPassQueryEngineError::ArithmeticOverflowError,
+                format!("{} {} {} overflows", lhs, operator.symbol(), rhs)
+            )
+        })
+    })
+}
+
+// Evaluates whether the first array is a subset or a superset of the second array, or shares an element with it.
+// The elements are compared by `equal`, so the arrays may come from the user data of different tags.
+fn evaluate_set(operator: Operator, operands: &[Val], text: &TextOptions) -> Result<Val, QueryEngineError> {
+    let arrays = operands
+        .iter()
+        .map(|operand| match operand {
+            Val::Array(items) => Ok(items),
+            _ => Err(unexpected_value("the operands must be arrays", operand)),
+        })
+        .collect::<Result<Vec<&Vec<Val>>, _>>()?;
+    let contains = |items: &[Val], val: &Val| items.iter().any(|item| equal(item, val, text));
+    let (lhs, rhs) = (arrays[0], arrays[1]);
+
+    let result = match operator {
+        Operator::SubsetOf => lhs.iter().all(|item| contains(rhs, item)),
+        Operator::SupersetOf => rhs.iter().all(|item| contains(lhs, item)),
+        _ => lhs.iter().any(|item| contains(rhs, item)),
+    };
+    Ok(Val::Bool(result))
+}
+
+// Compares two values for equality, numbers by their exact value, e.g. 2.0 == 2,
+// strings after the normalization of the text options, and arrays and objects element by element.
+fn equal(lhs: &Val, rhs: &Val, text: &TextOptions) -> bool {
+    match (lhs, rhs) {
+        (Val::Array(a), Val::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equal(a, b, text)),
+        (Val::Object(a), Val::Object(b)) => {
+            a.len() == b.len() &&
+                a.iter().all(|entry| rhs.get(&entry.key).is_some_and(|val| equal(&entry.val, val, text)))
+        }
+        (Val::Str(a), Val::Str(b)) => text.equal(a, b),
+        _ => match (to_decimal(lhs), to_decimal(rhs)) {
+            (Some(a), Some(b)) => a == b,
+            _ => lhs == rhs,
+        },
+    }
+}
+
+// Takes an integer as a decimal, so that integers and decimals can be compared and combined.
+fn to_decimal(val: &Val) -> Option<Decimal> {
+    match val {
+        Val::Int(i) => Some(Decimal::from_i64(*i)),
+        Val::Decimal(d) => Some(*d),
+        _ => None,
+    }
+}
+
+// Evaluates the dates followed by their date format, e.g. `[date, date_format]` into the age in years,
+// or `[date, other_date, date_format]` into whether the date is before the other date.
+// A date may be a date time, e.g. in RFC 3339, which the day based operators take the date of.
+fn evaluate_date(operator: Operator, operands: &[Val], now: &LocalDateTime) -> Result<Val, QueryEngineError> {
+    let (date_format, dates) = match operands.split_last() {
+        Some((Val::Str(date_format), dates)) => (date_format, dates),
+        _ => {
+            return Err(unexpected_value("the last operand must be the date format", &Val::Array(operands.to_vec())));
+        }
+    };
+    let dates = dates
+        .iter()
+        .map(|date| parse_date_time(date, date_format))
+        .collect::<Result<Vec<LocalDateTime>, _>>()?;
+    let today = &now.date;
+
+    let result = match (operator, dates.as_slice()) {
+        (Operator::GetAge | Operator::AgeYears, [date]) => {
+            Val::Int(LocalDate::calculate_age_difference(&date.date, today))
+        }
+        // The engine counts the days of `get_day_diff` without the leap days, see `LocalDate::calculate_day_difference`
+        (Operator::GetDayDiff, [date]) => Val::Int(LocalDate::calculate_day_difference(&date.date, today)),
+        (Operator::DaysBetween, [from, to]) => {
+            Val::Int(LocalDate::calculate_calendar_day_difference(&from.date, &to.date))
+        }
+        (Operator::HoursSince, [date]) => Val::Int(LocalDateTime::calculate_hour_difference(date, now)),
+        (Operator::HoursBetween, [from, to]) => Val::Int(LocalDateTime::calculate_hour_difference(from, to)),
+        (Operator::DateBefore, [date, other]) => {
+            Val::Bool(LocalDateTime::calculate_second_difference(date, other) > 0)
+        }
+        (Operator::DateAfter, [date, other]) => {
+            Val::Bool(LocalDateTime::calculate_second_difference(date, other) < 0)
+        }
+        _ => {
+            return Err(
+                QueryEngineError::new(
+                    ZkPassQueryEngineError::UnexpectedOperatorError,
+                    format!("'{}' does not take {} dates", operator.symbol(), dates.len())
+                )
+            );
+        }
+    };
+    Ok(result)
+}
+
+fn parse_date_time(date: &Val, date_format: &str) -> Result<LocalDateTime, QueryEngineError> {
+    let date = match date {
+        Val::Str(date) => date,
+        _ => {
+            return Err(unexpected_value("the date must be a string", date));
+        }
+    };
+    LocalDateTime::parse_date_time(date, date_format).map_err(|err| {
+        QueryEngineError::new(
+            ZkPassQueryEngineError::UnexpectedValueError,
+            format!("the date '{}' is invalid for the format '{}': {:?}", date, date_format, err)
+        )
+    })
+}
+
+// Whether a dvar or an ivar has a value, is present with a null value, or is missing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Presence {
+    Value,
+    Null,
+    Missing,
+}
+
+fn compile_data_var(path: &str) -> Result<jmespath::Expression<'static>, QueryEngineError> {
+    jmespath::compile(path).map_err(|err| QueryEngineError::new(ZkPassQueryEngineError::DataVarParsingError, err.to_string()))
+}
+
+// Checks whether a dvar path that resolves to null ends with a field or an index that is present
+// in its parent, i.e. the user data has an explicit null there. Any other path is taken as missing.
+fn is_present(path: &str, ast: &Ast, user_data: &Value) -> bool {
+    let (parent_path, last) = match ast {
+        Ast::Subexpr { offset, rhs, .. } => {
+            match path.get(..*offset) {
+                Some(prefix) => (prefix.trim_end(), rhs.as_ref()),
+                None => {
+                    return false;
+                }
+            }
+        }
+        _ => ("@", ast),
+    };
+    let parent = match jmespath::compile(parent_path).ok().and_then(|parent| parent.search(user_data).ok()) {
+        Some(parent) => parent,
+        None => {
+            return false;
+        }
+    };
+    match last {
+        Ast::Field { name, .. } => parent.as_object().is_some_and(|object| object.contains_key(name)),
+        Ast::Index { idx, .. } => {
+            parent.as_array().is_some_and(|items| {
+                let len = items.len() as i64;
+                let idx = *idx as i64;
+                (-len..len).contains(&idx)
+            })
+        }
+        _ => false,
+    }
+}
+
+// Finds the shortest prefix of a dvar path that resolves to nothing, e.g. "accounts.c" of
+// "accounts.c.balance", by walking the subexpressions on the left of the path.
+// Returns `None` when the path has no shorter prefix that resolves to nothing.
+fn missing_prefix<'a>(path: &'a str, ast: &Ast, user_data: &Value) -> Option<&'a str> {
+    let mut missing = None;
+    let mut ast = ast;
+    while let Ast::Subexpr { offset, lhs, .. } = ast {
+        let prefix = path.get(..*offset)?.trim_end();
+        let resolved = jmespath::compile(prefix).ok()?.search(user_data).ok()?;
+        if !resolved.is_null() {
+            break;
+        }
+        missing = Some(prefix);
+        ast = lhs;
+    }
+    missing
+}
+
+fn val_from_variable(variable: &Variable) -> Result<Val, QueryEngineError> {
+    match variable {
+        Variable::String(s) => Ok(Val::Str(s.clone())),
+        Variable::Bool(b) => Ok(Val::Bool(*b)),
+        Variable::Number(n) => Val::from_number(n).ok_or_else(|| {
+            QueryEngineError::new(ZkPassQueryEngineError::UnexpectedValueError, format!("unsupported number {}", n))
+        }),
+        Variable::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| val_from_variable(item))
+                .collect::<Result<Vec<Val>, _>>()?;
+            Ok(Val::Array(items))
+        }
+        Variable::Object(map) => {
+            let entries = map
+                .iter()
+                .map(|(key, item)| Ok(Entry { key: key.clone(), val: val_from_variable(item)? }))
+                .collect::<Result<Vec<Entry>, QueryEngineError>>()?;
+            Ok(Val::Object(entries))
+        }
+        // A null dvar is told apart from a missing one by `find_data_var`
+        Variable::Null => Ok(Val::Null),
+        _ => Err(QueryEngineError::new(ZkPassQueryEngineError::UnexpectedValueError, "unsupported user data value")),
+    }
+}
+
+// Evaluates `[string, start]` or `[string, start, length]` into the characters of the string from `start`,
+// at most `length` of them. A range beyond the end of the string is cut at the end.
+fn evaluate_substring(operands: &[Val]) -> Result<Val, QueryEngineError> {
+    let s = match &operands[0] {
+        Val::Str(s) => s,
+        operand => {
+            return Err(unexpected_value("the first operand must be a string", operand));
+        }
+    };
+    let counts = operands[1..]
+        .iter()
+        .map(|operand| match operand {
+            Val::Int(i) if *i >= 0 => Ok(usize::try_from(*i).unwrap_or(usize::MAX)),
+            _ => Err(unexpected_value("the start and the length must be non-negative integers", operand)),
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+    let length = counts.get(1).copied().unwrap_or(usize::MAX);
+    Ok(Val::Str(s.chars().skip(counts[0]).take(length).collect()))
+}
+
+// Evaluates `[value, bounds]` into the range of the ascending bounds that the value is in,
+// e.g. 742 with the bounds [0, 600, 700, 800] is "700-800", a value below the first bound is "<0"
+// and a value from the last bound is "800+". With `[value, bounds, labels]` it is the label of the range,
+// one label for each of the ranges from below the first bound to beyond the last one.
+fn evaluate_bucket(operands: &[Val]) -> Result<Val, QueryEngineError> {
+    let value = to_decimal(&operands[0]).ok_or_else(|| unexpected_value("the value must be a number", &operands[0]))?;
+    let bounds = match &operands[1] {
+        Val::Array(bounds) if !bounds.is_empty() => bounds.iter().map(to_decimal).collect::<Option<Vec<Decimal>>>(),
+        _ => None,
+    };
+    let bounds = match bounds {
+        Some(bounds) if bounds.windows(2).all(|pair| pair[0] < pair[1]) => bounds,
+        _ => {
+            return Err(unexpected_value("the bounds must be a non-empty array of ascending numbers", &operands[1]));
+        }
+    };
+    let index = bounds.partition_point(|bound| *bound <= value);
+
+    match operands.get(2) {
+        Some(Val::Array(labels)) if labels.len() == bounds.len() + 1 => Ok(labels[index].clone()),
+        Some(labels) => {
+            Err(unexpected_value(&format!("the labels must be an array of {} labels", bounds.len() + 1), labels))
+        }
+        None if index == 0 => Ok(Val::Str(format!("<{}", bounds[0]))),
+        None if index == bounds.len() => Ok(Val::Str(format!("{}+", bounds[index - 1]))),
+        None => Ok(Val::Str(format!("{}-{}", bounds[index - 1], bounds[index]))),
+    }
+}
+
+// Evaluates `[value, step]` into the largest multiple of the step that is not greater than the value,
+// e.g. 55250000 with the step 10000000 is 50000000. The result is an integer when both operands are.
+fn evaluate_round_down(operands: &[Val]) -> Result<Val, QueryEngineError> {
+    let zero = Decimal::from_i64(0);
+    let value = to_decimal(&operands[0]).ok_or_else(|| unexpected_value("the value must be a number", &operands[0]))?;
+    let step = match to_decimal(&operands[1]) {
+        Some(step) if step > zero => step,
+        _ => {
+            return Err(unexpected_value("the step must be a positive number", &operands[1]));
+        }
+    };
+    let result = value
+        .checked_rem(&step)
+        .and_then(|remainder| if remainder < zero { remainder.checked_add(&step) } else { Some(remainder) })
+        .and_then(|remainder| value.checked_sub(&remainder));
+    let result = match (&operands[0], &operands[1]) {
+        (Val::Int(_), Val::Int(_)) => result.and_then(|result| result.to_i64()).map(Val::Int),
+        _ => result.map(Val::Decimal),
+    };
+    result.ok_or_else(|| {
+        QueryEngineError::new(
+            ZkPassQueryEngineError::ArithmeticOverflowError,
+            format!("rounding {} down to a multiple of {} overflows", value, step)
+        )
+    })
+}
+
+// Evaluates `[value, salt, hash]` or `[value, salt, [hashes...]]` into whether the salted hash of the value,
+// as of `commitment::blind`, is one of the hashes, so the DVR does not disclose the values it looks for,
+// as long as they cannot be guessed and hashed with the salt of the DVR.
+// The salt and the hashes are validated by the parser.
+fn evaluate_blinded_in(operands: &[Val]) -> Result<Val, QueryEngineError> {
+    let salt = match &operands[1] {
+        Val::Str(salt) => commitment::parse_salt(salt).ok(),
+        _ => None,
+    };
+    let salt = salt.ok_or_else(|| QueryEngineError::new(ZkPassQueryEngineError::SaltError, "the salt is malformed"))?;
+    let hash = Val::Str(commitment::commit(&salt, &operands[0]));
+    let found = match &operands[2] {
+        Val::Array(hashes) => hashes.contains(&hash),
+        other => *other == hash,
+    };
+    Ok(Val::Bool(found))
+}
+
+// Evaluates `[string, other_string]` into their Jaro-Winkler similarity of 4 digits, e.g. 0.9611, or
+// `[string, other_string, threshold]` into whether their exact similarity is at least the threshold.
+// The names are compared in the folded case of the text options, with their whitespace runs collapsed,
+// e.g. "BUDI  SANTOSO" and "Budi Santoso" have a similarity of 1.
+fn evaluate_similarity(operator: Operator, operands: &[Val], text: &TextOptions) -> Result<Val, QueryEngineError> {
+    let strings = operands[..2]
+        .iter()
+        .map(|operand| match operand {
+            Val::Str(s) => Ok(text.fold_case(s).split_whitespace().collect::<Vec<&str>>().join(" ")),
+            _ => Err(unexpected_value("the names must be strings", operand)),
+        })
+        .collect::<Result<Vec<String>, _>>()?;
+    let score = similarity::jaro_winkler(&strings[0], &strings[1]);
+    if operator == Operator::Similarity {
+        return Ok(Val::Decimal(score.to_decimal()));
+    }
+
+    match to_decimal(&operands[2]) {
+        Some(threshold) if threshold >= Decimal::from_i64(0) && threshold <= Decimal::from_i64(1) => {
+            Ok(Val::Bool(score.cmp_decimal(&threshold).is_ge()))
+        }
+        _ => Err(unexpected_value("the threshold must be a number from 0 to 1", &operands[2])),
+    }
+}
+
+// Evaluates `[string, keep_prefix, keep_suffix]` or `[string, keep_prefix, keep_suffix, mask_char]` into
+// the string with each character between the kept prefix and suffix replaced by the mask character, "*" by default,
+// e.g. "1231770104567" with 0 and 4 is "*********4567". The suffix may be given as a delimiter instead, which keeps
+// the suffix from its last occurrence, e.g. "dewi@yahoo.com" with 1 and "@" is "d***@yahoo.com".
+// When the kept characters would be the whole string, the whole string is masked instead.
+fn evaluate_mask(operands: &[Val]) -> Result<Val, QueryEngineError> {
+    let chars: Vec<char> = match &operands[0] {
+        Val::Str(s) => s.chars().collect(),
+        operand => {
+            return Err(
+                QueryEngineError::new(
+                    ZkPassQueryEngineError::MaskingError,
+                    format!("only a string can be masked, found {}", operand.to_json())
+                )
+            );
+        }
+    };
+    let prefix = match &operands[1] {
+        Val::Int(i) if *i >= 0 => usize::try_from(*i).unwrap_or(usize::MAX),
+        operand => {
+            return Err(unexpected_value("the kept prefix must be a non-negative integer", operand));
+        }
+    };
+    let suffix = match &operands[2] {
+        Val::Int(i) if *i >= 0 => usize::try_from(*i).unwrap_or(usize::MAX),
+        Val::Str(delimiter) if !delimiter.is_empty() => {
+            let delimiter: Vec<char> = delimiter.chars().collect();
+            chars
+                .windows(delimiter.len())
+                .rposition(|window| window == delimiter.as_slice())
+                .map_or(0, |index| chars.len() - index)
+        }
+        operand => {
+            return Err(unexpected_value("the kept suffix must be a non-negative integer or a delimiter", operand));
+        }
+    };
+    let mask = match operands.get(3) {
+        None => '*',
+        Some(Val::Str(mask)) if mask.chars().count() == 1 => mask.chars().next().unwrap(),
+        Some(operand) => {
+            return Err(unexpected_value("the mask character must be a string of one character", operand));
+        }
+    };
+
+    let (prefix, suffix) = if prefix.saturating_add(suffix) >= chars.len() { (0, 0) } else { (prefix, suffix) };
+    let masked = chars
+        .iter()
+        .enumerate()
+        .map(|(index, c)| if index < prefix || index >= chars.len() - suffix { *c } else { mask })
+        .collect();
+    Ok(Val::Str(masked))
+}
+
+// Compares two strings after the normalization of the text options, and folds their case
+// with the case folding of the text options when the operator ignores the case.
+fn compare(operator: Operator, lhs: &Val, rhs: &Val, text: &TextOptions) -> Result<bool, QueryEngineError> {
+    // A decimal is compared with an integer by its exact value, e.g. 2.0 == 2
+    if matches!(lhs, Val::Decimal(_)) || matches!(rhs, Val::Decimal(_)) {
+        if let (Some(a), Some(b)) = (to_decimal(lhs), to_decimal(rhs)) {
+            match operator {
+                Operator::Eq => return Ok(a == b),
+                Operator::Gt => return Ok(a > b),
+                Operator::Ge => return Ok(a >= b),
+                Operator::Lt => return Ok(a < b),
+                Operator::Le => return Ok(a <= b),
+                _ => {}
+            }
+        }
+    }
+
+    match (operator, lhs, rhs) {
+        (Operator::Eq, Val::Str(a), Val::Str(b)) => Ok(text.equal(a, b)),
+        (Operator::Eq, Val::Int(a), Val::Int(b)) => Ok(a == b),
+        (Operator::Eq, Val::Bool(a), Val::Bool(b)) => Ok(a == b),
+        (Operator::EqIgnoreCase, Val::Str(a), Val::Str(b)) => Ok(text.fold_case(a) == text.fold_case(b)),
+        (Operator::Contains, Val::Str(a), Val::Str(b)) => Ok(text.normalize(a).contains(text.normalize(b).as_ref())),
+        (Operator::ContainsIgnoreCase, Val::Str(a), Val::Str(b)) => Ok(text.lowercase(a).contains(&text.lowercase(b))),
+        (Operator::StartsWith, Val::Str(a), Val::Str(b)) => {
+            Ok(text.normalize(a).starts_with(text.normalize(b).as_ref()))
+        }
+        (Operator::StartsWithIgnoreCase, Val::Str(a), Val::Str(b)) => {
+            Ok(text.lowercase(a).starts_with(&text.lowercase(b)))
+        }
+        (Operator::EndsWith, Val::Str(a), Val::Str(b)) => Ok(text.normalize(a).ends_with(text.normalize(b).as_ref())),
+        (Operator::EndsWithIgnoreCase, Val::Str(a), Val::Str(b)) => Ok(text.lowercase(a).ends_with(&text.lowercase(b))),
+        (Operator::Matches | Operator::MatchesIgnoreCase, Val::Str(a), Val::Str(b)) => {
+            let ignore_case = operator == Operator::MatchesIgnoreCase;
+            // The regex folds the case itself, so only the letters of the case folding are mapped here
+            let prepare = |s: &str| {
+                let s = text.normalize(s);
+                if ignore_case { text.prefold(&s).into_owned() } else { s.into_owned() }
+            };
+            let regex = Regex::new(&prepare(b), ignore_case).map_err(|err| {
+                QueryEngineError::new(
+                    ZkPassQueryEngineError::UnexpectedValueError,
+                    format!("invalid pattern '{}': {}", b, err)
+                )
+            })?;
+            Ok(regex.is_match(&prepare(a)))
+        }
+        (Operator::Gt, Val::Int(a), Val::Int(b)) => Ok(a > b),
+        (Operator::Ge, Val::Int(a), Val::Int(b)) => Ok(a >= b),
+        (Operator::Lt, Val::Int(a), Val::Int(b)) => Ok(a < b),
+        (Operator::Le, Val::Int(a), Val::Int(b)) => Ok(a <= b),
+        _ => Err(
+            QueryEngineError::new(
+                ZkPassQueryEngineError::UnexpectedValueError,
+                format!("'{}' cannot compare {} with {}", operator.symbol(), lhs.to_json(), rhs.to_json())
+            )
+        ),
+    }
+}
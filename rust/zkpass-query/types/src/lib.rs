@@ -3,14 +3,55 @@ use serde_json::Value;
 use std::collections::HashMap;
 use chrono::prelude::*;
 
+#[cfg(feature = "preview")]
+pub mod ast;
+#[cfg(feature = "preview")]
+pub mod commitment;
+#[cfg(feature = "preview")]
+pub mod decimal;
+pub mod error;
+#[cfg(feature = "preview")]
+pub mod evaluator;
+#[cfg(feature = "preview")]
+pub mod parser;
+#[cfg(feature = "preview")]
+pub mod pseudonym;
+#[cfg(feature = "preview")]
+pub mod regex;
+#[cfg(feature = "preview")]
+pub mod similarity;
+#[cfg(feature = "preview")]
+pub mod text;
+#[cfg(feature = "preview")]
+pub mod typed;
 mod tests;
 
+#[cfg(feature = "preview")]
+pub use evaluator::{evaluate_query, PreviewInput};
+#[cfg(feature = "preview")]
+pub use decimal::Decimal;
+pub use error::QueryEngineError;
+#[cfg(feature = "preview")]
+pub use parser::{parse_query, parse_query_for_engine};
+#[cfg(feature = "preview")]
+pub use pseudonym::DvrVerifyingKey;
+#[cfg(feature = "preview")]
+pub use typed::OutputTypeError;
+
+#[cfg(feature = "preview")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "preview")]
+use typed::ValDeserializer;
+
 mod date_format {
     pub const DDMMYYYY: &str = "DD/MM/YYYY";
     pub const MMDDYYYY: &str = "MM/DD/YYYY";
+    pub const YYYYMMDD: &str = "YYYY-MM-DD";
+    pub const YYYYMMDDHHMMSS: &str = "YYYY-MM-DDThh:mm:ss";
+    pub const RFC3339: &str = "RFC3339";
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub enum ZkPassQueryEngineError {
     UnhandledPanicError,
     UnexpectedValueError,
@@ -46,6 +87,30 @@ pub enum ZkPassQueryEngineError {
     CyclesLimitExceededError,
     EnvironmentError,
     SerializationError,
+    #[cfg(feature = "preview")]
+    ArithmeticOverflowError,
+    #[cfg(feature = "preview")]
+    DivisionByZeroError,
+    #[cfg(feature = "preview")]
+    ReferenceTimeError,
+    #[cfg(feature = "preview")]
+    ItemVarParsingError,
+    #[cfg(feature = "preview")]
+    ConstStatementParsingError,
+    #[cfg(feature = "preview")]
+    SwitchStatementParsingError,
+    #[cfg(feature = "preview")]
+    DefineStatementParsingError,
+    #[cfg(feature = "preview")]
+    SaltError,
+    #[cfg(feature = "preview")]
+    VerifierIdentityError,
+    #[cfg(feature = "preview")]
+    MaskingError,
+    #[cfg(feature = "preview")]
+    TextStatementParsingError,
+    #[cfg(feature = "preview")]
+    InexactNumberError,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -62,6 +127,15 @@ impl SymbolTable {
         self.table.push(entry);
     }
 
+    // Add an entry to the table.
+    // If the key already exists, it replaces the old value while maintaining the original order.
+    pub fn set(&mut self, entry: Entry) {
+        match self.table.iter_mut().find(|existing| existing.key == entry.key) {
+            Some(existing) => existing.val = entry.val,
+            None => self.table.push(entry),
+        }
+    }
+
     pub fn find(&self, key: &str) -> Option<&Val> {
         self.table.iter().find_map(|entry| {
             if entry.key == key {
@@ -71,6 +145,21 @@ impl SymbolTable {
             }
         })
     }
+
+    // Serializes the table into a JSON object, keeping the order of the entries.
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self.table
+            .iter()
+            .map(|entry| format!("\"{}\":{}", escape_string(&entry.key), entry.val.to_json()))
+            .collect();
+        format!("{{{}}}", entries.join(","))
+    }
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub type OutputTable = SymbolTable;
@@ -86,9 +175,108 @@ pub enum Val {
     Int(i64),
     Bool(bool),
     Array(Vec<Val>),
+
+    /// An exact decimal, e.g. of a JSON number with a fraction.
+    /// A JSON number is read through an f64, so a query or user data with a number that it would round is rejected,
+    /// and `OutputReader::from_json` reads such a number again from its literal.
+    #[cfg(feature = "preview")]
+    Decimal(Decimal),
+
+    /// A JSON object, as entries in the order they were read
+    #[cfg(feature = "preview")]
+    Object(Vec<Entry>),
+
+    #[cfg(feature = "preview")]
+    Null,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+impl Val {
+    // Serializes the value into its JSON representation.
+    pub fn to_json(&self) -> String {
+        match self {
+            Val::Str(s) => format!("\"{}\"", escape_string(s)),
+            Val::Int(i) => i.to_string(),
+            Val::Bool(b) => b.to_string(),
+            Val::Array(array) => {
+                let items: Vec<String> = array.iter().map(|val| val.to_json()).collect();
+                format!("[{}]", items.join(","))
+            }
+            #[cfg(feature = "preview")]
+            Val::Decimal(d) => d.to_string(),
+            #[cfg(feature = "preview")]
+            Val::Object(entries) => {
+                let items: Vec<String> = entries
+                    .iter()
+                    .map(|entry| format!("\"{}\":{}", escape_string(&entry.key), entry.val.to_json()))
+                    .collect();
+                format!("{{{}}}", items.join(","))
+            }
+            #[cfg(feature = "preview")]
+            Val::Null => String::from("null"),
+        }
+    }
+
+    // Converts a JSON value, recursively.
+    // The objects and nulls are only supported by the preview feature.
+    pub fn from_value(value: Value) -> Result<Self, OutputReaderError> {
+        let val = match value {
+            Value::String(s) => Val::Str(s),
+            Value::Bool(b) => Val::Bool(b),
+            Value::Number(n) => Val::from_number(&n).ok_or(OutputReaderError::UnsupportedTypeError)?,
+            Value::Array(array) => {
+                Val::Array(array.into_iter().map(Val::from_value).collect::<Result<Vec<Val>, _>>()?)
+            }
+            #[cfg(feature = "preview")]
+            Value::Object(map) => {
+                let entries = map
+                    .into_iter()
+                    .map(|(key, value)| Ok(Entry { key, val: Val::from_value(value)? }))
+                    .collect::<Result<Vec<Entry>, _>>()?;
+                Val::Object(entries)
+            }
+            #[cfg(feature = "preview")]
+            Value::Null => Val::Null,
+            #[cfg(not(feature = "preview"))]
+            _ => {
+                return Err(OutputReaderError::UnsupportedTypeError);
+            }
+        };
+        Ok(val)
+    }
+
+    // Gets the value of a key, when the value is an object.
+    #[cfg(feature = "preview")]
+    pub fn get(&self, key: &str) -> Option<&Val> {
+        match self {
+            Val::Object(entries) => entries.iter().find(|entry| entry.key == key).map(|entry| &entry.val),
+            _ => None,
+        }
+    }
+
+    // Finds a nested value given a path of keys and array indexes, e.g. "address.city" or "items[1].name".
+    #[cfg(feature = "preview")]
+    pub fn find_path(&self, path: &str) -> Option<&Val> {
+        path.split('.').try_fold(self, |val, segment| {
+            let (key, indexes) = split_path_segment(segment)?;
+            index_val(val.get(key)?, indexes)
+        })
+    }
+
+    // Converts a JSON number into an integer, or into a decimal when it is not an i64.
+    // A number with more fractional digits than `Decimal::MAX_SCALE` is not converted, rather than truncated.
+    // The decimals are only supported by the preview feature.
+    pub fn from_number(n: &serde_json::Number) -> Option<Self> {
+        match n.as_i64() {
+            Some(i) => Some(Val::Int(i)),
+            #[cfg(feature = "preview")]
+            None => Decimal::from_str_exact(&n.to_string()).ok().map(Val::Decimal),
+            #[cfg(not(feature = "preview"))]
+            None => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 pub struct Entry {
     pub key: String,
     pub val: Val
@@ -125,52 +313,43 @@ impl OutputReader {
         match node {
             Value::Object(map) => {
                 for (key, valnode) in map {
-                    match valnode {
-                        Value::String(s) => {
-                            table.add(Entry{key, val: Val::Str(s)});
-                        },
-                        Value::Bool(b) => {
-                            table.add(Entry{key, val: Val::Bool(b)});
-                        },
-                        Value::Number(n) => {
-                            let i = n.as_i64().unwrap();
-                            table.add(Entry{key, val: Val::Int(i)});
-                        }
-                        Value::Array(a) => {
-                            let mut array = Vec::new();
-                            for valnode in a {
-                                match valnode {
-                                    Value::String(s) => {
-                                        array.push(Val::Str(s));
-                                    },
-                                    Value::Bool(b) => {
-                                        array.push(Val::Bool(b));
-                                    },
-                                    Value::Number(n) => {
-                                        let i = n.as_i64().unwrap();
-                                        array.push(Val::Int(i));
-                                    }
-                                    _ => {
-                                        return Err(OutputReaderError::UnsupportedTypeError);
-                                    }
-                                }
-                            }
-                            table.add(Entry{key, val: Val::Array(array)});
-                        }
-                        _ => {
-                            return Err(OutputReaderError::UnsupportedTypeError);
-                        }
-                    }
+                    table.add(Entry{key, val: Val::from_value(valnode)?});
                 }
             },
             _ => {
                 return Err(OutputReaderError::ExpectingObjectError);
             }
         }
+        #[cfg(feature = "preview")]
+        table.read_exact_numbers(json)?;
 
         Ok(table)
     }
 
+    // Replaces the numbers that serde_json rounded through an f64 with the exact decimals of their literals,
+    // e.g. the 3.333333333333333333 output of `{"/": [10, 3.0]}`.
+    #[cfg(feature = "preview")]
+    fn read_exact_numbers(&mut self, json: &str) -> Result<(), OutputReaderError> {
+        for (location, literal) in decimal::inexact_numbers(json) {
+            let exact = Decimal::from_str_exact(&literal).map_err(|_| OutputReaderError::UnsupportedTypeError)?;
+            let rounded = serde_json::from_str(&literal).ok().and_then(|n| Val::from_number(&n));
+            let mut tokens = location.split('/').skip(1).map(|token| token.replace("~1", "/").replace("~0", "~"));
+            let entry = tokens.next().and_then(|key| self.index_map.get(&key).copied());
+            let val = entry.and_then(|index| {
+                tokens.try_fold(&mut self.entries[index].val, |val, token| match val {
+                    Val::Object(entries) => entries.iter_mut().find(|entry| entry.key == token).map(|entry| &mut entry.val),
+                    Val::Array(array) => array.get_mut(token.parse::<usize>().ok()?),
+                    _ => None,
+                })
+            });
+            // A duplicate key keeps its last value, which may not be this literal.
+            if let Some(val) = val.filter(|val| rounded.as_ref() == Some(&**val)) {
+                *val = Val::Decimal(exact);
+            }
+        }
+        Ok(())
+    }
+
     // Add an entry to the collection.
     // If the key already exists, it replaces the old entry while maintaining the original order.
     pub fn add(&mut self, entry: Entry) {
@@ -222,6 +401,69 @@ impl OutputReader {
         }
     }
 
+    // Finds a value given a path of keys and array indexes into the nested values,
+    // e.g. "address.city" or "items[1].name".
+    #[cfg(feature = "preview")]
+    pub fn find_path(&self, path: &str) -> Option<&Val> {
+        let (first, rest) = match path.split_once('.') {
+            Some((first, rest)) => (first, Some(rest)),
+            None => (path, None),
+        };
+        let (key, indexes) = split_path_segment(first)?;
+        let val = index_val(self.find(key)?, indexes)?;
+        match rest {
+            Some(rest) => val.find_path(rest),
+            None => Some(val),
+        }
+    }
+
+    // Finds a decimal, or an integer as a decimal.
+    #[cfg(feature = "preview")]
+    pub fn find_decimal(&self, key: &str) -> Option<Decimal> {
+        match self.find(key)? {
+            Val::Decimal(d) => Some(*d),
+            Val::Int(i) => Some(Decimal::from_i64(*i)),
+            _ => None,
+        }
+    }
+
+    // Gets the reference time that the query was evaluated at, when the verifier supplied one.
+    pub fn reference_time(&self) -> Option<ReferenceTime> {
+        ReferenceTime::from_rfc3339(&self.find_string(ReferenceTime::OUTPUT_NAME)?)
+    }
+
+    // Gets the identity of the verifier that the pseudonyms of the outputs are scoped to.
+    #[cfg(feature = "preview")]
+    pub fn verifier_id(&self) -> Option<String> {
+        self.find_string(pseudonym::VERIFIER_ID_OUTPUT_NAME)
+    }
+
+    // Checks that the pseudonyms of the outputs are scoped to the verifier that signed the DVR with the key,
+    // e.g. the `dvr_verifying_key` of the proof metadata.
+    #[cfg(feature = "preview")]
+    pub fn is_scoped_to(&self, key: &DvrVerifyingKey) -> bool {
+        self.verifier_id().is_some_and(|verifier_id| verifier_id == pseudonym::verifier_id(key))
+    }
+
+    // Checks that a revealed value and its hex salt open the commitment output of a `commit` operator.
+    #[cfg(feature = "preview")]
+    pub fn verify_opening(&self, key: &str, salt: &str, val: &Val) -> bool {
+        self.find_string(key).is_some_and(|commitment| commitment::verify_opening(&commitment, salt, val))
+    }
+
+    // Deserializes the outputs into an application type, e.g. a struct with a field per output.
+    // A missing or mistyped output is reported with its path, e.g. "items[1].name".
+    // The reserved outputs of the reference time and the verifier id are left out, so the type needs no field
+    // for them; they are read with `reference_time` and `verifier_id`.
+    #[cfg(feature = "preview")]
+    pub fn into_typed<T: DeserializeOwned>(self) -> Result<T, OutputTypeError> {
+        let entries = self.entries
+            .into_iter()
+            .filter(|entry| entry.key != ReferenceTime::OUTPUT_NAME && entry.key != pseudonym::VERIFIER_ID_OUTPUT_NAME)
+            .collect();
+        T::deserialize(ValDeserializer::new(Val::Object(entries)))
+    }
+
     // Enumerate the entries in the order they were added.
     pub fn enumerate(&self) -> Vec<&Entry> {
         self.entries.iter().collect()
@@ -235,6 +477,32 @@ impl OutputReader {
     }
 }
 
+// Splits a path segment like "items[1][0]" into its key and the indexes after it.
+#[cfg(feature = "preview")]
+fn split_path_segment(segment: &str) -> Option<(&str, &str)> {
+    let (key, indexes) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+    if key.is_empty() {
+        return None;
+    }
+    Some((key, indexes))
+}
+
+// Applies indexes like "[1][0]" to nested arrays.
+#[cfg(feature = "preview")]
+fn index_val<'a>(mut val: &'a Val, mut indexes: &str) -> Option<&'a Val> {
+    while !indexes.is_empty() {
+        let (index, rest) = indexes.strip_prefix('[')?.split_once(']')?;
+        val = match val {
+            Val::Array(array) => array.get(index.parse::<usize>().ok()?)?,
+            _ => {
+                return None;
+            }
+        };
+        indexes = rest;
+    }
+    Some(val)
+}
+
 pub fn escape_string(input: &str) -> String {
     // Check if any character needs to be escaped.
     if !input.chars().any(|c| matches!(c, '\"' | '\\' | '\n' | '\r' | '\t' | '\u{0008}' | '\u{000C}')) {
@@ -258,10 +526,20 @@ pub fn escape_string(input: &str) -> String {
     escaped_string
 }
 
+//
+// Errors of parsing a date or a date time
+//
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum LocalDateError {
+    UnsupportedFormatError,
+    MalformedDateError,
+    OutOfRangeError,
+}
+
 //
 // Struct to hold date information
 //
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
 pub struct LocalDate {
     pub day: u8,   // 1-31
     pub month: u8, // 1-12
@@ -269,9 +547,33 @@ pub struct LocalDate {
 }
 
 impl LocalDate {
-    // Constants for the number of days passed in each months.
+    // Constants for the number of days passed in each months of a common year.
     const MONTHS_DAYS_PASSED: [i64; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
 
+    // Determines if the date format is supported by `try_parse_date`.
+    pub fn is_date_format(date_format: &str) -> bool {
+        matches!(
+            date_format,
+            date_format::DDMMYYYY
+                | date_format::MMDDYYYY
+                | date_format::YYYYMMDD
+                | date_format::YYYYMMDDHHMMSS
+                | date_format::RFC3339
+        )
+    }
+
+    // Creates a LocalDate, validating the month and the day of the month.
+    pub fn new(year: u32, month: u32, day: u32) -> Result<Self, LocalDateError> {
+        let year = u16::try_from(year).map_err(|_| LocalDateError::OutOfRangeError)?;
+        if year == 0 || !(1..=12).contains(&month) {
+            return Err(LocalDateError::OutOfRangeError);
+        }
+        if day == 0 || day > LocalDate::days_in_month(year, month as u8) as u32 {
+            return Err(LocalDateError::OutOfRangeError);
+        }
+        Ok(LocalDate { day: day as u8, month: month as u8, year })
+    }
+
     // Converts a date string into a LocalDate struct,
     // given a date format and allowing for different delimiters ('/', '-', '.')
     pub fn parse_date(date_str: &str, date_format: &str) -> Option<Self> {
@@ -303,15 +605,39 @@ impl LocalDate {
         Some(LocalDate{ day, month, year })
     }
 
-    // Gets the current date and converts into u32
-    pub fn now() -> u32 {
-        let local_date = Local::now().date_naive();
-        let now = LocalDate {
-            day: local_date.day() as u8,
-            month: local_date.month() as u8,
-            year: local_date.year() as u16,
+    // Converts a date string into a validated LocalDate struct, given a date format.
+    // Unlike `parse_date`, it supports the ISO 8601 and RFC 3339 formats, rejects a month or a day
+    // that is out of range and reports why the date is invalid.
+    // The numeric parts of the date may be delimited by '/', '-' or '.',
+    // and the time of day of a date time format is ignored.
+    pub fn try_parse_date(date_str: &str, date_format: &str) -> Result<Self, LocalDateError> {
+        let (day, month, year) = match date_format {
+            date_format::DDMMYYYY => {
+                let [day, month, year] = split_numbers(date_str, &['/', '-', '.'])?;
+                (day, month, year)
+            }
+            date_format::MMDDYYYY => {
+                let [month, day, year] = split_numbers(date_str, &['/', '-', '.'])?;
+                (day, month, year)
+            }
+            date_format::YYYYMMDD => {
+                let [year, month, day] = split_numbers(date_str, &['/', '-', '.'])?;
+                (day, month, year)
+            }
+            date_format::YYYYMMDDHHMMSS | date_format::RFC3339 => {
+                return LocalDateTime::parse_date_time(date_str, date_format).map(|date_time| date_time.date);
+            }
+            _ => {
+                return Err(LocalDateError::UnsupportedFormatError);
+            }
         };
-        now.to_u32()
+        LocalDate::new(year, month, day)
+    }
+
+    // Gets the current date and converts into u32.
+    // It depends on the clock and timezone of the host, see `ReferenceTime` for a verifiable date.
+    pub fn now() -> u32 {
+        LocalDateTime::now().date.to_u32()
     }
 
     // Converts a LocalDate to a u32.
@@ -332,6 +658,21 @@ impl LocalDate {
         LocalDate { day, month, year }
     }
 
+    // Determines if the year is a leap year of the Gregorian calendar.
+    pub fn is_leap_year(year: u16) -> bool {
+        (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    }
+
+    // Gets the number of days in the month of the year.
+    pub fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
+            2 if LocalDate::is_leap_year(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
     /// Returns the number of days that is the sum of:
     /// 1. Year days (365 days per year, does not count the leap year)
     /// 2. Month days (days passed from the beginning of the year till the current month). Example:
@@ -341,12 +682,29 @@ impl LocalDate {
     /// 3. Days passed.
     ///
     /// Example: 2024-03-15 = (2024 * 365) + 59 + 15 = 738_394
-    fn get_days(self: &Self) -> i64 {
+    fn get_days(&self) -> i64 {
         let month_days = LocalDate::MONTHS_DAYS_PASSED[(self.month - 1) as usize];
         let year_days = 365 * self.year as i64;
         year_days + month_days + self.day as i64
     }
 
+    /// Returns the number of days since 0001-01-01 (day 1) of the Gregorian calendar, which is `get_days`
+    /// counting the leap days:
+    /// 1. Year days (365 days per year, plus one day per leap year) of the years passed.
+    /// 2. Month days, plus one day from March in a leap year.
+    /// 3. Days passed.
+    ///
+    /// Example: 2024-03-15 = (2023 * 365 + 490) + 60 + 15 = 738_960
+    fn get_calendar_days(&self) -> i64 {
+        let years = self.year as i64 - 1;
+        let leap_days = years / 4 - years / 100 + years / 400;
+        let mut month_days = LocalDate::MONTHS_DAYS_PASSED[(self.month - 1) as usize];
+        if self.month > 2 && LocalDate::is_leap_year(self.year) {
+            month_days += 1;
+        }
+        years * 365 + leap_days + month_days + self.day as i64
+    }
+
     // Calculates the age difference between two LocalDate instances.
     pub fn calculate_age_difference(a: &LocalDate, b: &LocalDate) -> i64 {
         let (earlier, later) = if LocalDate::is_later(a, b) {
@@ -361,10 +719,8 @@ impl LocalDate {
         // or if it's the same month but the later date's day is before the earlier date's day,
         // then subtract one year from the age.
         if later.month < earlier.month || (later.month == earlier.month && later.day < earlier.day) {
-            if age > 0 {
-                // Prevent underflow
-                age -= 1;
-            }
+            // Prevent underflow
+            age = age.saturating_sub(1);
         }
 
         age as i64
@@ -374,14 +730,169 @@ impl LocalDate {
     /// The result is positive if the first date is earlier than the second date.
     /// The result is negative if the first date is later than the second date.
     pub fn calculate_day_difference(check_date: &LocalDate, reference_date: &LocalDate) -> i64 {
-        let check_date_days = check_date.get_days() as i64;
-        let reference_date_days = reference_date.get_days() as i64;
+        let check_date_days = check_date.get_days();
+        let reference_date_days = reference_date.get_days();
 
         reference_date_days - check_date_days
     }
 
+    /// Calculates the day difference between two LocalDate instances, counting the leap days.
+    /// The result is positive if the first date is earlier than the second date.
+    /// The result is negative if the first date is later than the second date.
+    pub fn calculate_calendar_day_difference(check_date: &LocalDate, reference_date: &LocalDate) -> i64 {
+        reference_date.get_calendar_days() - check_date.get_calendar_days()
+    }
+
     // Determines if date 'a' is later than date 'b'.
     fn is_later(a: &LocalDate, b: &LocalDate) -> bool {
         (a.year, a.month, a.day) > (b.year, b.month, b.day)
     }
 }
+
+//
+// Struct to hold date and time information.
+// The UTC offset is `None` for a date time without a timezone, which is then
+// taken to be in the timezone of the date time it is compared with.
+//
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct LocalDateTime {
+    pub date: LocalDate,
+    pub hour: u8,                // 0-23
+    pub minute: u8,              // 0-59
+    pub second: u8,              // 0-59
+    pub utc_offset: Option<i32>  // seconds east of UTC, e.g. 25200 for UTC+07:00
+}
+
+impl LocalDateTime {
+    // Converts a date time string into a LocalDateTime struct, given a date format.
+    // A date of a date only format is taken at midnight.
+    pub fn parse_date_time(date_time_str: &str, date_format: &str) -> Result<Self, LocalDateError> {
+        match date_format {
+            date_format::RFC3339 => {
+                let date_time = DateTime::parse_from_rfc3339(date_time_str)
+                    .map_err(|_| LocalDateError::MalformedDateError)?;
+                LocalDateTime::from_date_time(&date_time)
+            }
+            date_format::YYYYMMDDHHMMSS => {
+                let (date, time) = date_time_str.split_once('T').ok_or(LocalDateError::MalformedDateError)?;
+                let date = LocalDate::try_parse_date(date, date_format::YYYYMMDD)?;
+                let [hour, minute, second] = split_numbers(time, &[':'])?;
+                if hour > 23 || minute > 59 || second > 59 {
+                    return Err(LocalDateError::OutOfRangeError);
+                }
+                Ok(LocalDateTime {
+                    date,
+                    hour: hour as u8,
+                    minute: minute as u8,
+                    second: second as u8,
+                    utc_offset: None,
+                })
+            }
+            _ => {
+                let date = LocalDate::try_parse_date(date_time_str, date_format)?;
+                Ok(LocalDateTime { date, hour: 0, minute: 0, second: 0, utc_offset: None })
+            }
+        }
+    }
+
+    // Gets the current date time of the host, in the timezone of the host.
+    pub fn now() -> Self {
+        LocalDateTime::from_date_time(&Local::now().fixed_offset()).unwrap()
+    }
+
+    fn from_date_time(date_time: &DateTime<FixedOffset>) -> Result<Self, LocalDateError> {
+        let date = LocalDate::new(date_time.year() as u32, date_time.month(), date_time.day())?;
+        Ok(LocalDateTime {
+            date,
+            hour: date_time.hour() as u8,
+            minute: date_time.minute() as u8,
+            second: date_time.second() as u8,
+            utc_offset: Some(date_time.offset().local_minus_utc()),
+        })
+    }
+
+    // Returns the number of seconds since 0001-01-01T00:00:00 in UTC, given the UTC offset of the date time.
+    fn get_seconds(&self, utc_offset: i32) -> i64 {
+        let seconds = (self.hour as i64) * 3600 + (self.minute as i64) * 60 + (self.second as i64);
+        self.date.get_calendar_days() * 86_400 + seconds - utc_offset as i64
+    }
+
+    /// Calculates the second difference between two LocalDateTime instances.
+    /// The result is positive if the first date time is earlier than the second date time.
+    pub fn calculate_second_difference(check_date_time: &LocalDateTime, reference_date_time: &LocalDateTime) -> i64 {
+        let check_offset = check_date_time.utc_offset.or(reference_date_time.utc_offset).unwrap_or(0);
+        let reference_offset = reference_date_time.utc_offset.or(check_date_time.utc_offset).unwrap_or(0);
+
+        reference_date_time.get_seconds(reference_offset) - check_date_time.get_seconds(check_offset)
+    }
+
+    /// Calculates the hour difference between two LocalDateTime instances, in whole hours.
+    /// The result is positive if the first date time is earlier than the second date time.
+    pub fn calculate_hour_difference(check_date_time: &LocalDateTime, reference_date_time: &LocalDateTime) -> i64 {
+        LocalDateTime::calculate_second_difference(check_date_time, reference_date_time) / 3600
+    }
+}
+
+// Splits the parts of a date or a time by the delimiters, and parses each part as a number of up to 4 digits.
+fn split_numbers(s: &str, delimiters: &[char]) -> Result<[u32; 3], LocalDateError> {
+    let mut numbers = [0; 3];
+    let mut parts = s.split(delimiters);
+    for number in numbers.iter_mut() {
+        let part = parts.next().ok_or(LocalDateError::MalformedDateError)?;
+        if part.is_empty() || part.len() > 4 || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(LocalDateError::MalformedDateError);
+        }
+        *number = part.parse().map_err(|_| LocalDateError::MalformedDateError)?;
+    }
+    if parts.next().is_some() {
+        return Err(LocalDateError::MalformedDateError);
+    }
+    Ok(numbers)
+}
+
+///
+/// The reference time of a query evaluation, supplied by the verifier.
+/// The date operators evaluate "today" at this time, in the timezone of the verifier,
+/// instead of reading the clock of the prover host.
+///
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ReferenceTime {
+    /// The Unix timestamp, in seconds
+    pub timestamp: i64,
+
+    /// The offset of the timezone from UTC, in seconds, e.g. 25200 for UTC+07:00
+    pub utc_offset: i32,
+}
+
+impl ReferenceTime {
+    // The output name under which the reference time is committed, as an RFC 3339 string.
+    pub const OUTPUT_NAME: &'static str = "_zkpass_reference_time_";
+
+    fn to_date_time(self) -> Option<DateTime<FixedOffset>> {
+        let offset = FixedOffset::east_opt(self.utc_offset)?;
+        Some(DateTime::from_timestamp(self.timestamp, 0)?.with_timezone(&offset))
+    }
+
+    // Gets the date time of the reference time in its timezone.
+    pub fn now(&self) -> Option<LocalDateTime> {
+        LocalDateTime::from_date_time(&self.to_date_time()?).ok()
+    }
+
+    // Gets the date of the reference time in its timezone.
+    pub fn today(&self) -> Option<LocalDate> {
+        Some(self.now()?.date)
+    }
+
+    // Formats the reference time as RFC 3339, e.g. "2024-03-15T10:00:00+07:00".
+    pub fn to_rfc3339(&self) -> Option<String> {
+        Some(self.to_date_time()?.to_rfc3339_opts(SecondsFormat::Secs, true))
+    }
+
+    pub fn from_rfc3339(s: &str) -> Option<Self> {
+        let date_time = DateTime::parse_from_rfc3339(s).ok()?;
+        Some(ReferenceTime {
+            timestamp: date_time.timestamp(),
+            utc_offset: date_time.offset().local_minus_utc(),
+        })
+    }
+}
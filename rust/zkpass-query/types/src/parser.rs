@@ -0,0 +1,1318 @@
+//
+// Offline parser and validator of the zkPass query language.
+//
+// The parser turns the JSON query of a DVR into the typed `Query` AST.
+// Instead of stopping at the first problem, it reports every malformed
+// statement or expression it finds as a located `QueryEngineError`, so a
+// verifier can reject a query before the DVR is signed.
+//
+use jmespath::ast::Ast;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{Case, Definition, Expression, Literal, LiteralSet, Operator, Quantifier, Query, Statement};
+use crate::error::{pointer, QueryEngineError};
+use crate::commitment::{self, SaltError};
+use crate::decimal::inexact_numbers;
+use crate::pseudonym;
+use crate::regex::Regex;
+use crate::text::{CaseFolding, Normalization, TextOptions};
+use crate::{date_format, Decimal, LocalDate, ReferenceTime, Val, ZkPassQueryEngineError};
+
+pub(crate) mod keyword {
+    pub const ASSIGN: &str = "assign";
+    pub const OUTPUT: &str = "output";
+    pub const IF: &str = "if";
+    pub const CONDITION: &str = "condition";
+    pub const THEN: &str = "then";
+    pub const ELSE: &str = "else";
+    pub const SWITCH: &str = "switch";
+    pub const CASES: &str = "cases";
+    pub const DEFAULT: &str = "default";
+    pub const DVAR: &str = "dvar";
+    pub const LVAR: &str = "lvar";
+    pub const IVAR: &str = "ivar";
+    pub const CONST: &str = "const";
+    pub const DEFINE: &str = "define";
+    pub const PARAMS: &str = "params";
+    pub const BODY: &str = "body";
+    pub const REF: &str = "ref";
+    pub const PARAM: &str = "param";
+    pub const TEXT: &str = "text";
+    pub const NORMALIZATION: &str = "normalization";
+    pub const CASE_FOLDING: &str = "case_folding";
+    pub const ALL: &str = "and";
+    pub const ANY: &str = "or";
+}
+
+// The JMESPath functions that a dvar path may call.
+// jmespath computes numbers in floating point, so "abs", "avg", "ceil", "floor", "sum" and "to_number",
+// which return a new number, are left out; the arithmetic operators of the query are exact.
+// "max", "min", "sort" and their "_by" forms return the numbers of the user data unchanged, but order them
+// as f64s, so numbers that differ beyond 15 significant digits may be taken as equal.
+const DVAR_FUNCTIONS: [&str; 20] = [
+    "contains",
+    "ends_with",
+    "join",
+    "keys",
+    "length",
+    "map",
+    "max",
+    "max_by",
+    "merge",
+    "min",
+    "min_by",
+    "not_null",
+    "reverse",
+    "sort",
+    "sort_by",
+    "starts_with",
+    "to_array",
+    "to_string",
+    "type",
+    "values",
+];
+
+///
+/// Parses and validates a query script, which is encoded in JSON string format.
+/// Returns the typed query, or all the errors found in the query.
+///
+pub fn parse_query(query: &str) -> Result<Query, Vec<QueryEngineError>> {
+    parse(query, false)
+}
+
+///
+/// Parses and validates a query script like `parse_query`, and also reports every statement,
+/// expression or operator that the engine of the zkvm proof method does not run, so a verifier
+/// can reject a query that the proof method would fail on before the DVR is signed.
+/// The engine runs the `assign`, `output` and `if` statements, the `dvar` and `lvar` variables,
+/// integer, string and boolean literals, and the operators from `==` to `get_day_diff`.
+///
+pub fn parse_query_for_engine(query: &str) -> Result<Query, Vec<QueryEngineError>> {
+    parse(query, true)
+}
+
+fn parse(query: &str, engine: bool) -> Result<Query, Vec<QueryEngineError>> {
+    let value: Value = serde_json::from_str(query).map_err(|err| {
+        vec![QueryEngineError::new(ZkPassQueryEngineError::QueryParsingError, err.to_string()).at("")]
+    })?;
+
+    let mut parser = Parser { engine, ..Parser::default() };
+    for (location, literal) in inexact_numbers(query) {
+        parser.report(
+            ZkPassQueryEngineError::InexactNumberError,
+            &location,
+            Some(&literal),
+            format!("the number {} cannot be represented exactly", literal)
+        );
+    }
+    let statements = match value.as_array() {
+        Some(statements) => parser.parse_block(statements, ""),
+        None => {
+            parser.report(
+                ZkPassQueryEngineError::DvrNotAnArrayError,
+                "",
+                None,
+                "the query must be an array of statements"
+            );
+            None
+        }
+    };
+
+    match statements {
+        Some(statements) if parser.errors.is_empty() => Ok(Query { statements }),
+        _ => Err(parser.errors),
+    }
+}
+
+#[derive(Default)]
+struct Parser {
+    errors: Vec<QueryEngineError>,
+    // Whether only the subset of the language that the proving engine runs is accepted
+    engine: bool,
+    local_vars: HashSet<String>,
+    constants: HashSet<String>,
+    // The names of the definitions, with their number of parameters when their signature is valid
+    definitions: HashMap<String, Option<usize>>,
+    // The parameters of the definition whose body is being parsed
+    params: Vec<String>,
+    // The definitions referred to by the body being parsed
+    references: Vec<String>,
+    // The number of if or switch blocks enclosing the statement being parsed
+    block_depth: usize,
+    // The number of array operators enclosing the expression being parsed
+    item_depth: usize,
+    // The options of the text statement, which normalize the strings of the literal sets
+    text: TextOptions,
+}
+
+impl Parser {
+    fn report(
+        &mut self,
+        kind: ZkPassQueryEngineError,
+        location: &str,
+        token: Option<&str>,
+        message: impl Into<String>
+    ) {
+        let mut error = QueryEngineError::new(kind, message).at(location);
+        error.token = token.map(String::from);
+        self.errors.push(error);
+    }
+
+    // Reports a part of the query that the proving engine does not run, when only the engine subset is accepted.
+    fn check_engine(&mut self, supported: bool, kind: ZkPassQueryEngineError, location: &str, token: &str, what: &str) -> bool {
+        if self.engine && !supported {
+            self.report(kind, location, Some(token), format!("the proving engine does not support {}", what));
+            return false;
+        }
+        true
+    }
+
+    // Parses every statement of the block, even after a malformed one, to collect all the errors.
+    fn parse_block(&mut self, values: &[Value], location: &str) -> Option<Vec<Statement>> {
+        let statements: Vec<Option<Statement>> = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| self.parse_statement(value, &pointer(location, &index.to_string())))
+            .collect();
+        statements.into_iter().collect()
+    }
+
+    fn parse_statement(&mut self, value: &Value, location: &str) -> Option<Statement> {
+        let object = match value.as_object() {
+            Some(object) if object.len() == 1 => object,
+            _ => {
+                self.report(
+                    ZkPassQueryEngineError::UnexpectedStmtError,
+                    location,
+                    None,
+                    "a statement must be an object with a single keyword"
+                );
+                return None;
+            }
+        };
+
+        let (keyword, body) = object.iter().next().unwrap();
+        let body_location = pointer(location, keyword);
+        let supported = matches!(keyword.as_str(), keyword::ASSIGN | keyword::OUTPUT | keyword::IF);
+        let what = format!("the '{}' statement", keyword);
+        if !self.check_engine(supported, ZkPassQueryEngineError::UnknownStmtKeywordParsingError, location, keyword, &what) {
+            return None;
+        }
+        match keyword.as_str() {
+            keyword::ASSIGN => {
+                let (name, expression) = self.parse_binding(
+                    body,
+                    &body_location,
+                    keyword,
+                    ZkPassQueryEngineError::AssignmentStatementExpectingOperandInObjectParsingError,
+                    ZkPassQueryEngineError::AssignmentStatementExpectingOneOperandParsingError
+                )?;
+                self.local_vars.insert(name.clone());
+                Some(Statement::Assign { name, expression })
+            }
+            keyword::OUTPUT => {
+                let (name, expression) = self.parse_binding(
+                    body,
+                    &body_location,
+                    keyword,
+                    ZkPassQueryEngineError::OutputStatementExpectingOperandInObjectParsingError,
+                    ZkPassQueryEngineError::OutputStatementExpectingOneOperandParsingError
+                )?;
+                if name == ReferenceTime::OUTPUT_NAME || name == pseudonym::VERIFIER_ID_OUTPUT_NAME {
+                    self.report(
+                        ZkPassQueryEngineError::OutputStatementParsingError,
+                        &pointer(&body_location, &name),
+                        Some(&name),
+                        "the output name is reserved for the reference time or the verifier identity"
+                    );
+                    return None;
+                }
+                Some(Statement::Output { name, expression })
+            }
+            keyword::IF => self.parse_if(body, &body_location),
+            keyword::SWITCH => self.parse_switch(body, &body_location),
+            keyword::CONST => self.parse_const(body, &body_location),
+            keyword::DEFINE => self.parse_define(body, &body_location),
+            keyword::TEXT => self.parse_text(body, location, &body_location),
+            _ => {
+                self.report(
+                    ZkPassQueryEngineError::UnknownStmtKeywordParsingError,
+                    location,
+                    Some(keyword),
+                    format!("unknown statement keyword '{}'", keyword)
+                );
+                None
+            }
+        }
+    }
+
+    // Parses the `{ "name": expression }` body shared by the assign and output statements.
+    fn parse_binding(
+        &mut self,
+        body: &Value,
+        location: &str,
+        keyword: &str,
+        not_an_object_error: ZkPassQueryEngineError,
+        not_one_operand_error: ZkPassQueryEngineError
+    ) -> Option<(String, Expression)> {
+        let body = match body.as_object() {
+            Some(body) => body,
+            None => {
+                self.report(not_an_object_error, location, Some(keyword), "expecting an object of { \"name\": expression }");
+                return None;
+            }
+        };
+        if body.len() != 1 {
+            self.report(
+                not_one_operand_error,
+                location,
+                Some(keyword),
+                format!("expecting one operand, found {}", body.len())
+            );
+            return None;
+        }
+
+        let (name, expression) = body.iter().next().unwrap();
+        let expression = self.parse_expression(expression, &pointer(location, name))?;
+        Some((name.clone(), expression))
+    }
+
+    fn parse_if(&mut self, body: &Value, location: &str) -> Option<Statement> {
+        let body = match body.as_object() {
+            Some(body) => body,
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::IfStatementExpectingOperandInObjectParsingError,
+                    location,
+                    Some(keyword::IF),
+                    "expecting an object of condition, then and else"
+                );
+                return None;
+            }
+        };
+        if body.len() > 3 {
+            self.report(
+                ZkPassQueryEngineError::IfStatementExpectingThreeOperandOrLessParsingError,
+                location,
+                Some(keyword::IF),
+                format!("expecting three operands or less, found {}", body.len())
+            );
+            return None;
+        }
+        for key in body.keys() {
+            if !matches!(key.as_str(), keyword::CONDITION | keyword::THEN | keyword::ELSE) {
+                self.report(
+                    ZkPassQueryEngineError::IfStatementUnknownKeywordParsingError,
+                    &pointer(location, key),
+                    Some(key),
+                    format!("unknown if statement keyword '{}'", key)
+                );
+            }
+        }
+
+        let condition = match body.get(keyword::CONDITION) {
+            Some(condition) => self.parse_expression(condition, &pointer(location, keyword::CONDITION)),
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::IfStatementMissingConditionParsingError,
+                    location,
+                    Some(keyword::IF),
+                    "missing the condition"
+                );
+                None
+            }
+        };
+        let then_block = match body.get(keyword::THEN) {
+            Some(block) => self.parse_nested_block(block, &pointer(location, keyword::THEN)),
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::IfStatementMissingThenBlockParsingError,
+                    location,
+                    Some(keyword::IF),
+                    "missing the then block"
+                );
+                None
+            }
+        };
+        let else_block = match body.get(keyword::ELSE) {
+            Some(block) => self.parse_nested_block(block, &pointer(location, keyword::ELSE)),
+            None => Some(Vec::new()),
+        };
+
+        Some(Statement::If {
+            condition: condition?,
+            then_block: then_block?,
+            else_block: else_block?,
+        })
+    }
+
+    fn parse_switch(&mut self, body: &Value, location: &str) -> Option<Statement> {
+        let error = ZkPassQueryEngineError::SwitchStatementParsingError;
+        let body = match body.as_object() {
+            Some(body) => body,
+            None => {
+                self.report(error, location, Some(keyword::SWITCH), "expecting an object of cases and default");
+                return None;
+            }
+        };
+        for key in body.keys() {
+            if !matches!(key.as_str(), keyword::CASES | keyword::DEFAULT) {
+                self.report(
+                    error.clone(),
+                    &pointer(location, key),
+                    Some(key),
+                    format!("unknown switch statement keyword '{}'", key)
+                );
+            }
+        }
+
+        let cases = match body.get(keyword::CASES).and_then(Value::as_array) {
+            Some(cases) if !cases.is_empty() => {
+                let location = pointer(location, keyword::CASES);
+                let cases: Vec<Option<Case>> = cases
+                    .iter()
+                    .enumerate()
+                    .map(|(index, case)| self.parse_case(case, &pointer(&location, &index.to_string())))
+                    .collect();
+                cases.into_iter().collect::<Option<Vec<Case>>>()
+            }
+            _ => {
+                self.report(error.clone(), location, Some(keyword::SWITCH), "expecting a non-empty array of cases");
+                None
+            }
+        };
+        let default_block = match body.get(keyword::DEFAULT) {
+            Some(block) => self.parse_nested_block(block, &pointer(location, keyword::DEFAULT)),
+            None => Some(Vec::new()),
+        };
+
+        // A case after a case that is always true never runs, which is most likely a mistake in the order of the cases
+        let cases = cases?;
+        if let Some(index) = cases.iter().position(|case| case.condition == Expression::Bool(true)) {
+            if index + 1 < cases.len() {
+                self.report(
+                    error,
+                    &pointer(&pointer(location, keyword::CASES), &(index + 1).to_string()),
+                    None,
+                    format!("the case is unreachable, because the condition of case {} is always true", index)
+                );
+                return None;
+            }
+        }
+
+        Some(Statement::Switch { cases, default_block: default_block? })
+    }
+
+    fn parse_case(&mut self, case: &Value, location: &str) -> Option<Case> {
+        let error = ZkPassQueryEngineError::SwitchStatementParsingError;
+        let case = match case.as_object() {
+            Some(case) => case,
+            None => {
+                self.report(error, location, None, "expecting an object of condition and then");
+                return None;
+            }
+        };
+        for key in case.keys() {
+            if !matches!(key.as_str(), keyword::CONDITION | keyword::THEN) {
+                self.report(error.clone(), &pointer(location, key), Some(key), format!("unknown case keyword '{}'", key));
+            }
+        }
+
+        let condition = match case.get(keyword::CONDITION) {
+            Some(condition) => self.parse_expression(condition, &pointer(location, keyword::CONDITION)),
+            None => {
+                self.report(error.clone(), location, None, "missing the condition");
+                None
+            }
+        };
+        let block = match case.get(keyword::THEN) {
+            Some(block) => self.parse_nested_block(block, &pointer(location, keyword::THEN)),
+            None => {
+                self.report(error.clone(), location, None, "missing the then block");
+                None
+            }
+        };
+
+        Some(Case { condition: condition?, block: block? })
+    }
+
+    fn parse_nested_block(&mut self, block: &Value, location: &str) -> Option<Vec<Statement>> {
+        match block.as_array() {
+            Some(statements) => {
+                self.block_depth += 1;
+                let block = self.parse_block(statements, location);
+                self.block_depth -= 1;
+                block
+            }
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::UnexpectedStmtError,
+                    location,
+                    None,
+                    "expecting an array of statements"
+                );
+                None
+            }
+        }
+    }
+
+    // A const list is declared once at the top level, so it is the same in every branch of the query.
+    fn parse_const(&mut self, body: &Value, location: &str) -> Option<Statement> {
+        let error = ZkPassQueryEngineError::ConstStatementParsingError;
+        if self.block_depth > 0 {
+            self.report(error, location, Some(keyword::CONST), "a const statement must be at the top level of the query");
+            return None;
+        }
+        let (name, values) = match body.as_object() {
+            Some(body) if body.len() == 1 => body.iter().next().unwrap(),
+            _ => {
+                self.report(error, location, Some(keyword::CONST), "expecting an object of { \"name\": [literals...] }");
+                return None;
+            }
+        };
+        let location = pointer(location, name);
+        if self.constants.contains(name) {
+            self.report(error, &location, Some(name), format!("the const '{}' is already declared", name));
+            return None;
+        }
+        let values = match values.as_array() {
+            Some(values) => values,
+            None => {
+                self.report(error, &location, Some(name), "the const value must be an array of literals");
+                return None;
+            }
+        };
+
+        let literals: Vec<Option<Literal>> = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let literal = self.literal_from_value(value);
+                if literal.is_none() {
+                    self.report(
+                        error.clone(),
+                        &pointer(&location, &index.to_string()),
+                        Some(&value.to_string()),
+                        "a const element must be a string, a number or a boolean"
+                    );
+                }
+                literal
+            })
+            .collect();
+        let literals = literals.into_iter().collect::<Option<Vec<Literal>>>()?;
+        self.constants.insert(name.clone());
+        Some(Statement::Const { name: name.clone(), values: LiteralSet::new(literals) })
+    }
+
+    // The text statement must come first, so the literal sets are normalized with its options
+    // and every string comparison of the query uses the same options.
+    fn parse_text(&mut self, body: &Value, statement_location: &str, location: &str) -> Option<Statement> {
+        let error = ZkPassQueryEngineError::TextStatementParsingError;
+        if statement_location != "/0" {
+            self.report(error, location, Some(keyword::TEXT), "a text statement must be the first statement of the query");
+            return None;
+        }
+        let body = match body.as_object() {
+            Some(body) => body,
+            None => {
+                self.report(error, location, Some(keyword::TEXT), "expecting an object of normalization and case_folding");
+                return None;
+            }
+        };
+
+        let mut valid = true;
+        for (key, value) in body {
+            let name = value.as_str().unwrap_or_default();
+            let known = match key.as_str() {
+                keyword::NORMALIZATION => {
+                    Normalization::from_name(name).map(|normalization| self.text.normalization = normalization).is_some()
+                }
+                keyword::CASE_FOLDING => {
+                    CaseFolding::from_name(name).map(|case_folding| self.text.case_folding = case_folding).is_some()
+                }
+                _ => {
+                    self.report(
+                        error.clone(),
+                        &pointer(location, key),
+                        Some(key),
+                        format!("unknown text statement keyword '{}'", key)
+                    );
+                    valid = false;
+                    continue;
+                }
+            };
+            if !known {
+                self.report(
+                    error.clone(),
+                    &pointer(location, key),
+                    Some(&value.to_string()),
+                    format!("unknown {} {}", key.replace('_', " "), value)
+                );
+                valid = false;
+            }
+        }
+        if !valid {
+            return None;
+        }
+        Some(Statement::Text(self.text))
+    }
+
+    // Definitions are declared at the top level, like const lists. The definitions of one define statement
+    // may refer to each other in any order, so all their names are known before their bodies are parsed.
+    fn parse_define(&mut self, body: &Value, location: &str) -> Option<Statement> {
+        let error = ZkPassQueryEngineError::DefineStatementParsingError;
+        if self.block_depth > 0 {
+            self.report(error, location, Some(keyword::DEFINE), "a define statement must be at the top level of the query");
+            return None;
+        }
+        let body = match body.as_object() {
+            Some(body) if !body.is_empty() => body,
+            _ => {
+                self.report(error, location, Some(keyword::DEFINE), "expecting an object of { \"name\": definition }");
+                return None;
+            }
+        };
+
+        let mut signatures = Vec::new();
+        for (name, definition) in body {
+            let definition_location = pointer(location, name);
+            if self.definitions.contains_key(name) {
+                self.report(
+                    error.clone(),
+                    &definition_location,
+                    Some(name),
+                    format!("the definition '{}' is already defined", name)
+                );
+                signatures.push(None);
+                continue;
+            }
+            let signature = self.parse_signature(definition, &definition_location);
+            self.definitions.insert(name.clone(), signature.as_ref().map(|(params, ..)| params.len()));
+            signatures.push(signature);
+        }
+
+        let mut references = HashMap::new();
+        let definitions: Vec<Option<Definition>> = body
+            .keys()
+            .zip(signatures)
+            .map(|(name, signature)| {
+                let (params, body, body_location) = signature?;
+                self.params = params;
+                let body = self.parse_expression(body, &body_location);
+                let params = std::mem::take(&mut self.params);
+                references.insert(name.clone(), std::mem::take(&mut self.references));
+                Some(Definition { name: name.clone(), params, body: body? })
+            })
+            .collect();
+
+        let mut is_acyclic = true;
+        for name in body.keys() {
+            if let Some(cycle) = find_cycle(name, &references) {
+                self.report(
+                    error.clone(),
+                    &pointer(location, name),
+                    Some(name),
+                    format!("the definition '{}' refers to itself: {}", name, cycle.join(" -> "))
+                );
+                is_acyclic = false;
+            }
+        }
+
+        let definitions = definitions.into_iter().collect::<Option<Vec<_>>>()?;
+        is_acyclic.then_some(Statement::Define { definitions })
+    }
+
+    // Parses a definition into its parameters, its body and the location of its body.
+    // A definition without parameters may be the body itself.
+    fn parse_signature<'a>(&mut self, definition: &'a Value, location: &str) -> Option<(Vec<String>, &'a Value, String)> {
+        let error = ZkPassQueryEngineError::DefineStatementParsingError;
+        let object = match definition.as_object() {
+            Some(object) if object.contains_key(keyword::BODY) => object,
+            _ => {
+                return Some((Vec::new(), definition, location.to_string()));
+            }
+        };
+        for key in object.keys() {
+            if !matches!(key.as_str(), keyword::PARAMS | keyword::BODY) {
+                self.report(
+                    error.clone(),
+                    &pointer(location, key),
+                    Some(key),
+                    format!("unknown definition keyword '{}'", key)
+                );
+            }
+        }
+
+        let params = match object.get(keyword::PARAMS) {
+            None => Vec::new(),
+            Some(Value::Array(values)) => {
+                let mut params: Vec<String> = Vec::new();
+                for (index, value) in values.iter().enumerate() {
+                    match value.as_str() {
+                        Some(param) if !params.iter().any(|existing| existing == param) => params.push(param.to_string()),
+                        _ => {
+                            self.report(
+                                error,
+                                &pointer(&pointer(location, keyword::PARAMS), &index.to_string()),
+                                Some(&value.to_string()),
+                                "a param must be a unique name"
+                            );
+                            return None;
+                        }
+                    }
+                }
+                params
+            }
+            Some(_) => {
+                self.report(error, &pointer(location, keyword::PARAMS), Some(keyword::PARAMS), "expecting an array of param names");
+                return None;
+            }
+        };
+        Some((params, &object[keyword::BODY], pointer(location, keyword::BODY)))
+    }
+
+    fn parse_expression(&mut self, value: &Value, location: &str) -> Option<Expression> {
+        match value {
+            Value::String(s) => Some(Expression::Str(s.clone())),
+            Value::Bool(b) => Some(Expression::Bool(*b)),
+            Value::Number(n) => match Val::from_number(n) {
+                Some(Val::Int(i)) => Some(Expression::Int(i)),
+                Some(Val::Decimal(d)) => {
+                    let what = "numbers with a fraction";
+                    self.check_engine(false, ZkPassQueryEngineError::UnexpectedValueError, location, &n.to_string(), what)
+                        .then_some(Expression::Decimal(d))
+                }
+                _ => {
+                    self.report(
+                        ZkPassQueryEngineError::UnexpectedValueError,
+                        location,
+                        Some(&n.to_string()),
+                        format!("unsupported number {}", n)
+                    );
+                    None
+                }
+            },
+            Value::Array(items) => {
+                let items: Vec<Option<Expression>> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| self.parse_expression(item, &pointer(location, &index.to_string())))
+                    .collect();
+                items.into_iter().collect::<Option<Vec<_>>>().map(Expression::Array)
+            }
+            Value::Object(object) => self.parse_object_expression(object, location),
+            Value::Null => {
+                self.report(ZkPassQueryEngineError::UnexpectedValueError, location, Some("null"), "unsupported null value");
+                None
+            }
+        }
+    }
+
+    fn parse_object_expression(&mut self, object: &Map<String, Value>, location: &str) -> Option<Expression> {
+        let is_defaulted = object.len() == 2 && object.contains_key(keyword::DVAR) && object.contains_key(keyword::DEFAULT);
+        if is_defaulted {
+            if !self.check_engine(false, ZkPassQueryEngineError::QueryParsingError, location, keyword::DEFAULT, "dvar defaults") {
+                return None;
+            }
+            return self.parse_data_var_or_default(&object[keyword::DVAR], &object[keyword::DEFAULT], location);
+        }
+        if object.len() != 1 {
+            self.report(
+                ZkPassQueryEngineError::QueryParsingError,
+                location,
+                None,
+                "an expression object must have a single dvar, lvar, ivar, const, ref, param or operator key, \
+                 or a dvar and a default key"
+            );
+            return None;
+        }
+
+        let (key, operands) = object.iter().next().unwrap();
+        let location = pointer(location, key);
+        if matches!(key.as_str(), keyword::IVAR | keyword::CONST | keyword::REF | keyword::PARAM) {
+            let what = format!("the '{}' expression", key);
+            if !self.check_engine(false, ZkPassQueryEngineError::QueryParsingError, &location, key, &what) {
+                return None;
+            }
+        }
+        match key.as_str() {
+            keyword::DVAR => self.parse_data_var(operands, &location),
+            keyword::LVAR => self.parse_local_var(operands, &location),
+            keyword::IVAR => self.parse_item_var(operands, &location),
+            keyword::CONST => self.parse_constant(operands, &location),
+            keyword::REF => self.parse_reference(operands, &location),
+            keyword::PARAM => self.parse_param(operands, &location),
+            _ => self.parse_operation(key, operands, &location),
+        }
+    }
+
+    fn parse_data_var(&mut self, value: &Value, location: &str) -> Option<Expression> {
+        self.parse_data_var_path(value, location).map(Expression::DataVar)
+    }
+
+    fn parse_data_var_or_default(&mut self, path: &Value, default: &Value, location: &str) -> Option<Expression> {
+        let path = self.parse_data_var_path(path, &pointer(location, keyword::DVAR));
+        let default = self.parse_expression(default, &pointer(location, keyword::DEFAULT));
+        Some(Expression::DataVarOrDefault { path: path?, default: Box::new(default?) })
+    }
+
+    fn parse_data_var_path(&mut self, value: &Value, location: &str) -> Option<String> {
+        let path = match value.as_str() {
+            Some(path) => path,
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::DataVarParsingError,
+                    location,
+                    Some(keyword::DVAR),
+                    "the dvar path must be a string"
+                );
+                return None;
+            }
+        };
+        if !path.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            self.report(
+                ZkPassQueryEngineError::DataVarNameNotStartingWithAlphabetError,
+                location,
+                Some(path),
+                format!("the dvar path '{}' must start with an alphabet", path)
+            );
+            return None;
+        }
+        let expression = match jmespath::compile(path) {
+            Ok(expression) => expression,
+            Err(err) => {
+                self.report(
+                    ZkPassQueryEngineError::DataVarParsingError,
+                    location,
+                    Some(path),
+                    format!("invalid dvar path '{}': {}", path, err)
+                );
+                return None;
+            }
+        };
+        if let Err(message) = check_functions(expression.as_ast()) {
+            self.report(
+                ZkPassQueryEngineError::DataVarParsingError,
+                location,
+                Some(path),
+                format!("invalid dvar path '{}': {}", path, message)
+            );
+            return None;
+        }
+        Some(path.to_string())
+    }
+
+    fn parse_local_var(&mut self, value: &Value, location: &str) -> Option<Expression> {
+        match value.as_str() {
+            Some(name) if self.local_vars.contains(name) => Some(Expression::LocalVar(name.to_string())),
+            Some(name) => {
+                self.report(
+                    ZkPassQueryEngineError::LocalVarParsingError,
+                    location,
+                    Some(name),
+                    format!("the lvar '{}' is not assigned before", name)
+                );
+                None
+            }
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::LocalVarParsingError,
+                    location,
+                    Some(keyword::LVAR),
+                    "the lvar name must be a string"
+                );
+                None
+            }
+        }
+    }
+
+    fn parse_item_var(&mut self, value: &Value, location: &str) -> Option<Expression> {
+        let path = match value.as_str() {
+            Some(path) if !path.is_empty() => path,
+            _ => {
+                self.report(
+                    ZkPassQueryEngineError::ItemVarParsingError,
+                    location,
+                    Some(keyword::IVAR),
+                    "the ivar path must be a non-empty string"
+                );
+                return None;
+            }
+        };
+        if self.item_depth == 0 {
+            self.report(
+                ZkPassQueryEngineError::ItemVarParsingError,
+                location,
+                Some(path),
+                format!("the ivar '{}' must be inside the predicate or the mapping of an array operator", path)
+            );
+            return None;
+        }
+        Some(Expression::ItemVar(path.to_string()))
+    }
+
+    // Converts a JSON string, number or boolean into a literal, with a string normalized by the text options.
+    fn literal_from_value(&self, value: &Value) -> Option<Literal> {
+        match literal_from_value(value)? {
+            Literal::Str(s) => Some(Literal::Str(self.text.normalize(&s).into_owned())),
+            literal => Some(literal),
+        }
+    }
+
+    fn parse_constant(&mut self, value: &Value, location: &str) -> Option<Expression> {
+        match value.as_str() {
+            Some(name) if self.constants.contains(name) => Some(Expression::Constant(name.to_string())),
+            Some(name) => {
+                self.report(
+                    ZkPassQueryEngineError::ConstStatementParsingError,
+                    location,
+                    Some(name),
+                    format!("the const '{}' is not declared before", name)
+                );
+                None
+            }
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::ConstStatementParsingError,
+                    location,
+                    Some(keyword::CONST),
+                    "the const name must be a string"
+                );
+                None
+            }
+        }
+    }
+
+    fn parse_reference(&mut self, value: &Value, location: &str) -> Option<Expression> {
+        let error = ZkPassQueryEngineError::DefineStatementParsingError;
+        let (name, args) = match value {
+            Value::String(name) => (name, &[][..]),
+            Value::Array(items) => match items.split_first() {
+                Some((Value::String(name), args)) => (name, args),
+                _ => {
+                    self.report(error, location, Some(keyword::REF), "expecting an array of a definition name and its arguments");
+                    return None;
+                }
+            },
+            _ => {
+                self.report(error, location, Some(keyword::REF), "expecting a definition name");
+                return None;
+            }
+        };
+        let arity = match self.definitions.get(name) {
+            Some(arity) => *arity,
+            None => {
+                self.report(error, location, Some(name), format!("the definition '{}' is not defined before", name));
+                return None;
+            }
+        };
+        if arity.is_some_and(|arity| arity != args.len()) {
+            self.report(
+                error,
+                location,
+                Some(name),
+                format!("the definition '{}' expects {} arguments, found {}", name, arity.unwrap(), args.len())
+            );
+            return None;
+        }
+        self.references.push(name.clone());
+
+        let args: Vec<Option<Expression>> = args
+            .iter()
+            .enumerate()
+            .map(|(index, arg)| self.parse_expression(arg, &pointer(location, &(index + 1).to_string())))
+            .collect();
+        let args = args.into_iter().collect::<Option<Vec<_>>>()?;
+        Some(Expression::Reference { name: name.clone(), args })
+    }
+
+    fn parse_param(&mut self, value: &Value, location: &str) -> Option<Expression> {
+        match value.as_str() {
+            Some(name) if self.params.iter().any(|param| param == name) => Some(Expression::Param(name.to_string())),
+            Some(name) => {
+                self.report(
+                    ZkPassQueryEngineError::DefineStatementParsingError,
+                    location,
+                    Some(name),
+                    format!("the param '{}' is not a parameter of the enclosing definition", name)
+                );
+                None
+            }
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::DefineStatementParsingError,
+                    location,
+                    Some(keyword::PARAM),
+                    "the param name must be a string"
+                );
+                None
+            }
+        }
+    }
+
+    fn parse_operation(&mut self, symbol: &str, operands: &Value, location: &str) -> Option<Expression> {
+        let operator = match Operator::from_symbol(symbol) {
+            Some(operator) => operator,
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::UnexpectedOperatorParsingError,
+                    location,
+                    Some(symbol),
+                    format!("unknown operator '{}'", symbol)
+                );
+                return None;
+            }
+        };
+        let what = format!("the '{}' operator", symbol);
+        if !self.check_engine(operator.is_engine(), ZkPassQueryEngineError::UnexpectedOperatorParsingError, location, symbol, &what) {
+            return None;
+        }
+        let operands = match operands.as_array() {
+            Some(operands) => operands,
+            None => {
+                self.report(
+                    ZkPassQueryEngineError::ExpectingOperandsInArrayParsingError,
+                    location,
+                    Some(symbol),
+                    format!("the operands of '{}' must be an array", symbol)
+                );
+                return None;
+            }
+        };
+
+        if operator.is_comparison() {
+            return self.parse_comparison(operator, operands, location);
+        }
+
+        let arity = match operator {
+            Operator::And | Operator::Or => 1..=usize::MAX,
+            Operator::Add | Operator::Mul => 2..=usize::MAX,
+            Operator::Count | Operator::Sum | Operator::Min | Operator::Max => 1..=2,
+            Operator::Length | Operator::Exists | Operator::IsNull => 1..=1,
+            Operator::Pseudonym => 1..=2,
+            Operator::Mask => 3..=4,
+            Operator::Similar | Operator::BlindedIn => 3..=3,
+            Operator::Substring | Operator::Bucket => 2..=3,
+            Operator::DaysBetween | Operator::HoursBetween | Operator::DateBefore | Operator::DateAfter => 3..=3,
+            _ => 2..=2,
+        };
+        if !self.check_operand_count(operator, operands.len(), arity, location) {
+            return None;
+        }
+        if operator.is_presence() && !is_variable(&operands[0]) {
+            self.report(
+                ZkPassQueryEngineError::UnexpectedValueError,
+                &pointer(location, "0"),
+                Some(operator.symbol()),
+                format!("the operand of '{}' must be a dvar or an ivar", operator.symbol())
+            );
+            return None;
+        }
+        if operator.is_date() {
+            self.check_date_format(operands.last().unwrap(), &pointer(location, &(operands.len() - 1).to_string()));
+        }
+        if operator == Operator::Bucket {
+            self.check_bounds(&operands[1..], location);
+        }
+        if operator == Operator::Similar {
+            self.check_threshold(&operands[2], &pointer(location, "2"));
+        }
+        // The salt and the hashes are the verifier's, so they must be in the DVR itself
+        if operator == Operator::BlindedIn && !self.check_blinded_set(&operands[1..], location) {
+            return None;
+        }
+        // The salt is named in the query and supplied by the holder, so it never appears in the query
+        if operator == Operator::Commit && !operands[1].is_string() {
+            self.report(
+                ZkPassQueryEngineError::SaltError,
+                &pointer(location, "1"),
+                Some(&operands[1].to_string()),
+                "the salt name must be a string"
+            );
+            return None;
+        }
+
+        let operands: Vec<Option<Expression>> = operands
+            .iter()
+            .enumerate()
+            .map(|(index, operand)| {
+                // A literal list of a membership operator is sorted once, here
+                if operator.is_membership() && index == 1 {
+                    let literals = operand
+                        .as_array()
+                        .and_then(|values| values.iter().map(|value| self.literal_from_value(value)).collect());
+                    if let Some(literals) = literals {
+                        return Some(Expression::Set(LiteralSet::new(literals)));
+                    }
+                }
+                // The operands after the array of an array operator are evaluated for each element
+                let per_item = operator.is_array() && index > 0;
+                self.item_depth += per_item as usize;
+                let expression = self.parse_expression(operand, &pointer(location, &index.to_string()));
+                self.item_depth -= per_item as usize;
+                expression
+            })
+            .collect();
+        let operands = operands.into_iter().collect::<Option<Vec<_>>>()?;
+        Some(Expression::Operation { operator, operands })
+    }
+
+    fn parse_comparison(&mut self, operator: Operator, operands: &[Value], location: &str) -> Option<Expression> {
+        if !self.check_operand_count(operator, operands.len(), 2..=3, location) {
+            return None;
+        }
+
+        let quantifier = match operands.get(2) {
+            None => Some(Quantifier::All),
+            Some(Value::String(quantifier)) if quantifier == keyword::ALL => Some(Quantifier::All),
+            Some(Value::String(quantifier)) if quantifier == keyword::ANY => Some(Quantifier::Any),
+            Some(_) => {
+                self.report(
+                    ZkPassQueryEngineError::ExpectingThirdOperandParsingError,
+                    &pointer(location, "2"),
+                    Some(&operands[2].to_string()),
+                    "the third operand must be either \"and\" or \"or\""
+                );
+                None
+            }
+        };
+        if matches!(operator, Operator::Matches | Operator::MatchesIgnoreCase) {
+            self.check_pattern(&operands[1], operator == Operator::MatchesIgnoreCase, &pointer(location, "1"));
+        }
+        let left = self.parse_expression(&operands[0], &pointer(location, "0"));
+        let right = self.parse_expression(&operands[1], &pointer(location, "1"));
+
+        Some(Expression::Comparison {
+            operator,
+            left: Box::new(left?),
+            right: Box::new(right?),
+            quantifier: quantifier?,
+        })
+    }
+
+    // A literal pattern is validated before the query runs, a computed one only when it is evaluated.
+    fn check_pattern(&mut self, pattern: &Value, ignore_case: bool, location: &str) {
+        if let Value::String(pattern) = pattern {
+            if let Err(err) = Regex::new(pattern, ignore_case) {
+                self.report(
+                    ZkPassQueryEngineError::UnexpectedValueError,
+                    location,
+                    Some(pattern),
+                    format!("invalid pattern '{}': {}", pattern, err)
+                );
+            }
+        }
+    }
+
+    // Literal bounds, and labels, of a bucket are validated before the query runs, computed ones only when
+    // they are evaluated. The bounds must be ascending numbers, with one more label than bounds.
+    fn check_bounds(&mut self, operands: &[Value], location: &str) {
+        let bounds = match operands[0].as_array() {
+            Some(bounds) => bounds,
+            None => {
+                return;
+            }
+        };
+        let numbers: Option<Vec<Literal>> = bounds
+            .iter()
+            .map(|bound| literal_from_value(bound).filter(|literal| matches!(literal, Literal::Number(_))))
+            .collect();
+        let is_ascending = numbers.is_some_and(|numbers| {
+            !numbers.is_empty() && numbers.windows(2).all(|pair| pair[0] < pair[1])
+        });
+        if !is_ascending {
+            self.report(
+                ZkPassQueryEngineError::UnexpectedValueError,
+                &pointer(location, "1"),
+                Some(&operands[0].to_string()),
+                "the bounds must be a non-empty array of ascending numbers"
+            );
+        }
+        if let Some(labels) = operands.get(1).and_then(Value::as_array) {
+            if labels.len() != bounds.len() + 1 {
+                self.report(
+                    ZkPassQueryEngineError::UnexpectedValueError,
+                    &pointer(location, "2"),
+                    Some(&operands[1].to_string()),
+                    format!("expecting {} labels for {} bounds, found {}", bounds.len() + 1, bounds.len(), labels.len())
+                );
+            }
+        }
+    }
+
+    // A literal threshold of similarity is validated before the query runs, a computed one only when it is evaluated.
+    fn check_threshold(&mut self, threshold: &Value, location: &str) {
+        if let Some(Literal::Number(number)) = literal_from_value(threshold) {
+            if number < Decimal::from_i64(0) || number > Decimal::from_i64(1) {
+                self.report(
+                    ZkPassQueryEngineError::UnexpectedValueError,
+                    location,
+                    Some(&threshold.to_string()),
+                    "the threshold must be a number from 0 to 1"
+                );
+            }
+        }
+    }
+
+    fn check_blinded_set(&mut self, operands: &[Value], location: &str) -> bool {
+        let salt = operands[0].as_str().map(commitment::parse_salt);
+        let message = match salt {
+            Some(Ok(_)) => None,
+            Some(Err(SaltError::ShortSaltError)) => {
+                Some(format!("the salt is shorter than {} bytes", commitment::MIN_SALT_LENGTH))
+            }
+            _ => Some(String::from("the salt must be a hex string")),
+        };
+        if let Some(message) = &message {
+            self.report(
+                ZkPassQueryEngineError::SaltError,
+                &pointer(location, "1"),
+                Some(&operands[0].to_string()),
+                message.as_str()
+            );
+        }
+
+        let hashes = match &operands[1] {
+            Value::Array(hashes) if !hashes.is_empty() => hashes.iter().collect(),
+            Value::Array(_) => Vec::new(),
+            hash => vec![hash],
+        };
+        let is_valid = !hashes.is_empty() && hashes.iter().all(|hash| hash.as_str().is_some_and(commitment::is_hash));
+        if !is_valid {
+            self.report(
+                ZkPassQueryEngineError::UnexpectedValueError,
+                &pointer(location, "2"),
+                Some(&operands[1].to_string()),
+                "the hashes must be a hex SHA-256 hash in lower case or a non-empty array of them"
+            );
+        }
+        message.is_none() && is_valid
+    }
+
+    // A literal date format is validated before the query runs, a computed one only when it is evaluated.
+    // The engine only parses the dates of `get_age` and `get_day_diff` with the original formats of `LocalDate::parse_date`.
+    fn check_date_format(&mut self, date_format: &Value, location: &str) {
+        if let Value::String(date_format) = date_format {
+            if !LocalDate::is_date_format(date_format) {
+                self.report(
+                    ZkPassQueryEngineError::UnexpectedValueError,
+                    location,
+                    Some(date_format),
+                    format!("unsupported date format '{}'", date_format)
+                );
+            } else {
+                let supported = matches!(date_format.as_str(), date_format::DDMMYYYY | date_format::MMDDYYYY);
+                let what = format!("the date format '{}'", date_format);
+                self.check_engine(supported, ZkPassQueryEngineError::UnexpectedValueError, location, date_format, &what);
+            }
+        }
+    }
+
+    fn check_operand_count(
+        &mut self,
+        operator: Operator,
+        count: usize,
+        arity: std::ops::RangeInclusive<usize>,
+        location: &str
+    ) -> bool {
+        let error = match count {
+            _ if count > *arity.end() => ZkPassQueryEngineError::UnexpectedOperatorParsingError,
+            0 if !arity.contains(&0) => ZkPassQueryEngineError::ExpectingFirstOperandParsingError,
+            1 if !arity.contains(&1) => ZkPassQueryEngineError::ExpectingSecondOperandParsingError,
+            2 if !arity.contains(&2) => ZkPassQueryEngineError::ExpectingThirdOperandParsingError,
+            _ => {
+                return true;
+            }
+        };
+
+        let expected = if arity.start() == arity.end() {
+            arity.start().to_string()
+        } else if *arity.end() == usize::MAX {
+            format!("at least {}", arity.start())
+        } else {
+            format!("{} to {}", arity.start(), arity.end())
+        };
+        self.report(
+            error,
+            location,
+            Some(operator.symbol()),
+            format!("'{}' expects {} operands, found {}", operator.symbol(), expected, count)
+        );
+        false
+    }
+}
+
+// Finds a chain of references from a definition back to itself, e.g. ["a", "b", "a"].
+fn find_cycle<'a>(start: &'a str, references: &'a HashMap<String, Vec<String>>) -> Option<Vec<&'a str>> {
+    fn visit<'a>(
+        name: &'a str,
+        start: &str,
+        references: &'a HashMap<String, Vec<String>>,
+        visited: &mut HashSet<&'a str>,
+        path: &mut Vec<&'a str>
+    ) -> bool {
+        path.push(name);
+        for next in references.get(name).into_iter().flatten() {
+            if next == start {
+                path.push(next);
+                return true;
+            }
+            if visited.insert(next) && visit(next, start, references, visited, path) {
+                return true;
+            }
+        }
+        path.pop();
+        false
+    }
+
+    let mut path = Vec::new();
+    visit(start, start, references, &mut HashSet::new(), &mut path).then_some(path)
+}
+
+// Checks whether a value is a `{ "dvar": ... }` or an `{ "ivar": ... }` object.
+fn is_variable(value: &Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|object| object.len() == 1 && (object.contains_key(keyword::DVAR) || object.contains_key(keyword::IVAR)))
+}
+
+// Checks that a dvar path only calls the supported JMESPath functions,
+// so an unknown function is reported before the query is run in the zkvm.
+fn check_functions(ast: &Ast) -> Result<(), String> {
+    match ast {
+        Ast::Function { name, args, .. } => {
+            if !DVAR_FUNCTIONS.contains(&name.as_str()) {
+                return Err(format!("the function '{}' is not supported", name));
+            }
+            args.iter().try_for_each(check_functions)
+        }
+        Ast::Comparison { lhs, rhs, .. } |
+        Ast::Projection { lhs, rhs, .. } |
+        Ast::And { lhs, rhs, .. } |
+        Ast::Or { lhs, rhs, .. } |
+        Ast::Subexpr { lhs, rhs, .. } => {
+            check_functions(lhs)?;
+            check_functions(rhs)
+        }
+        Ast::Condition { predicate, then, .. } => {
+            check_functions(predicate)?;
+            check_functions(then)
+        }
+        Ast::Expref { ast, .. } => check_functions(ast),
+        Ast::Flatten { node, .. } | Ast::Not { node, .. } | Ast::ObjectValues { node, .. } => check_functions(node),
+        Ast::MultiList { elements, .. } => elements.iter().try_for_each(check_functions),
+        Ast::MultiHash { elements, .. } => elements.iter().try_for_each(|pair| check_functions(&pair.value)),
+        Ast::Identity { .. } | Ast::Field { .. } | Ast::Index { .. } | Ast::Literal { .. } | Ast::Slice { .. } => Ok(()),
+    }
+}
+
+// Converts a JSON string, number or boolean into a literal.
+fn literal_from_value(value: &Value) -> Option<Literal> {
+    match value {
+        Value::String(s) => Some(Literal::Str(s.clone())),
+        Value::Bool(b) => Some(Literal::Bool(*b)),
+        Value::Number(n) => Literal::from_val(&Val::from_number(n)?),
+        _ => None,
+    }
+}
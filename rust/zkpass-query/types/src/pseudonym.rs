@@ -0,0 +1,73 @@
+//
+// Verifier-scoped pseudonyms of query values.
+//
+// The `pseudonym` operator outputs a deterministic identifier of a value, e.g. a customer ID,
+// that is the same every time the holder proves to the same verifier, and unrelated across verifiers.
+// It is derived as the hex of
+//
+//   SHA-256("zkpass-pseudonym-v1" || len(verifier_id) || verifier_id || len(context) || context || canonical(value))
+//
+// where each length is a big-endian u64 of the byte length, `verifier_id` is the identity of the
+// verifier, `context` is an optional label of the query (empty by default), and `canonical` is the
+// canonical JSON of the value of `commitment::canonical`.
+//
+// The verifier identity is not chosen by the caller: it is derived from the key that the DVR signature
+// was verified with, as the hex of
+//
+//   SHA-256("zkpass-verifier-id-v1" || len(x) || x || len(y) || y)
+//
+// so a pseudonym can only be scoped to the verifier that signed the DVR, and the verifier checks the
+// scope against the `dvr_verifying_key` of the proof metadata with `OutputReader::is_scoped_to`.
+//
+// Collisions: the pseudonyms are 256-bit hashes, so among n distinct values the probability that
+// two share a pseudonym is at most n^2 / 2^257, which is negligible for any number of holders.
+// Guessing: the derivation has no secret, so a verifier can test a guess of the value against
+// a pseudonym. It only hides values that are hard to guess; a value from a small or structured space,
+// e.g. a date of birth, can be found by trying all of them.
+//
+use serde::{ Deserialize, Serialize };
+use sha2::{ Digest, Sha256 };
+
+use crate::commitment::canonical;
+use crate::Val;
+
+// The output name under which the verifier identity is committed, so the verifier
+// can check that the pseudonyms are scoped to it.
+pub const VERIFIER_ID_OUTPUT_NAME: &str = "_zkpass_verifier_id_";
+
+const DOMAIN: &[u8] = b"zkpass-pseudonym-v1";
+
+const VERIFIER_ID_DOMAIN: &[u8] = b"zkpass-verifier-id-v1";
+
+///
+/// The public key that the zkPass Service verified the DVR signature with,
+/// with the `x` and `y` parameters of its `PublicKey`.
+///
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct DvrVerifyingKey {
+    pub x: String,
+    pub y: String,
+}
+
+// Derives the hex identity of the verifier that signed the DVR with the key.
+pub fn verifier_id(key: &DvrVerifyingKey) -> String {
+    hex::encode(hash_parts(VERIFIER_ID_DOMAIN, &[&key.x, &key.y]).finalize())
+}
+
+// Derives the hex pseudonym of a value for a verifier, within a context.
+pub fn pseudonym(verifier_id: &str, context: &str, val: &Val) -> String {
+    let mut hasher = hash_parts(DOMAIN, &[verifier_id, context]);
+    hasher.update(canonical(val).as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+// Hashes a domain and parts, each part prefixed by its length so that no two lists of parts collide.
+fn hash_parts(domain: &[u8], parts: &[&str]) -> Sha256 {
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher
+}
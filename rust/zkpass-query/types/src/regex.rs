@@ -0,0 +1,421 @@
+//
+// A linear-time regular expression subset of the zkPass query language.
+//
+// A pattern is compiled into a program of a Thompson NFA, which is run as a Pike VM:
+// all the threads advance together over the input, so matching takes O(input * program)
+// steps and never backtracks. This keeps the cycles bounded for any pattern and any user data,
+// as a risc0 or sp1 guest requires.
+//
+// The supported syntax is literals, '.', character classes like "[A-Z0-9_]" and "[^ ]",
+// the escapes \d, \w, \s (ASCII) and their negations \D, \W, \S, the quantifiers '*', '+',
+// '?', "{n}", "{n,}" and "{n,m}", alternation '|' and groups "(...)".
+// A pattern always matches the whole string, so there are no anchors.
+//
+use std::fmt;
+use caseless::default_case_fold_str;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegexError {
+    /// The character offset of the error in the pattern
+    pub position: usize,
+
+    /// A human readable description of the error
+    pub message: String,
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+#[derive(Clone, Debug)]
+pub struct Regex {
+    program: Vec<Inst>,
+    ignore_case: bool,
+}
+
+impl Regex {
+    // The maximum count of a bounded repetition, e.g. "{0,100}"
+    pub const MAX_REPETITION: u32 = 100;
+
+    // The maximum number of instructions of a compiled pattern
+    pub const MAX_PROGRAM_SIZE: usize = 10_000;
+
+    // Compiles a pattern. When `ignore_case` is set, the pattern and the input
+    // are compared by their Unicode case folding, like `~==` compares strings.
+    pub fn new(pattern: &str, ignore_case: bool) -> Result<Self, RegexError> {
+        let mut parser = PatternParser { chars: pattern.chars().collect(), position: 0, ignore_case };
+        let node = parser.parse_alternation()?;
+        if parser.position < parser.chars.len() {
+            return Err(parser.error("unmatched ')'"));
+        }
+
+        let mut program = Vec::new();
+        compile(&node, &mut program);
+        program.push(Inst::Match);
+        if program.len() > Regex::MAX_PROGRAM_SIZE {
+            return Err(RegexError { position: 0, message: String::from("the pattern is too large") });
+        }
+        Ok(Regex { program, ignore_case })
+    }
+
+    // Checks whether the pattern matches the whole text.
+    pub fn is_match(&self, text: &str) -> bool {
+        let text = if self.ignore_case { default_case_fold_str(text) } else { text.to_string() };
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        current.add(&self.program, 0);
+
+        for c in text.chars() {
+            for index in 0..current.pcs.len() {
+                let pc = current.pcs[index];
+                let matched = match &self.program[pc] {
+                    Inst::Char(expected) => *expected == c,
+                    Inst::Any => true,
+                    Inst::Class(class) => class.matches(c, self.ignore_case),
+                    _ => false,
+                };
+                if matched {
+                    next.add(&self.program, pc + 1);
+                }
+            }
+            if next.pcs.is_empty() {
+                return false;
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        current.pcs.iter().any(|pc| matches!(self.program[*pc], Inst::Match))
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    // Continues at both targets
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+#[derive(Clone, Debug)]
+struct Class {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl Class {
+    fn matches(&self, c: char, ignore_case: bool) -> bool {
+        let contains = |c: char| self.ranges.iter().any(|(start, end)| *start <= c && c <= *end);
+        // The input is case folded, so its upper case forms are tried as well, e.g. 'a' for "[A-Z]"
+        let found = contains(c) || (ignore_case && c.to_uppercase().any(contains));
+        found != self.negated
+    }
+
+    fn digit(negated: bool) -> Self {
+        Class { ranges: vec![('0', '9')], negated }
+    }
+
+    fn word(negated: bool) -> Self {
+        Class { ranges: vec![('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')], negated }
+    }
+
+    fn space(negated: bool) -> Self {
+        Class { ranges: vec![('\t', '\r'), (' ', ' ')], negated }
+    }
+}
+
+// The set of the threads at an input position, without duplicates.
+struct Threads {
+    pcs: Vec<usize>,
+    present: Vec<bool>,
+}
+
+impl Threads {
+    fn new(size: usize) -> Self {
+        Threads { pcs: Vec::new(), present: vec![false; size] }
+    }
+
+    // Adds a thread, following its jumps and splits until the instructions that consume input.
+    fn add(&mut self, program: &[Inst], pc: usize) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if self.present[pc] {
+                continue;
+            }
+            self.present[pc] = true;
+            match program[pc] {
+                Inst::Jump(target) => stack.push(target),
+                Inst::Split(first, second) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+                _ => self.pcs.push(pc),
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pcs.clear();
+        self.present.iter_mut().for_each(|present| *present = false);
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Char(char),
+    Any,
+    Class(Class),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat { node: Box<Node>, min: u32, max: Option<u32> },
+}
+
+struct PatternParser {
+    chars: Vec<char>,
+    position: usize,
+    ignore_case: bool,
+}
+
+impl PatternParser {
+    fn error(&self, message: &str) -> RegexError {
+        RegexError { position: self.position, message: message.to_string() }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, RegexError> {
+        let mut branches = vec![self.parse_concat()?];
+        while self.peek() == Some('|') {
+            self.position += 1;
+            branches.push(self.parse_concat()?);
+        }
+        Ok(if branches.len() == 1 { branches.pop().unwrap() } else { Node::Alternation(branches) })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_quantifier(atom)?);
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, RegexError> {
+        match self.next().unwrap() {
+            '(' => {
+                let node = self.parse_alternation()?;
+                if self.next() != Some(')') {
+                    return Err(self.error("missing ')'"));
+                }
+                Ok(node)
+            }
+            '[' => self.parse_class(),
+            '.' => Ok(Node::Any),
+            '\\' => self.parse_escape().map(|class_or_char| match class_or_char {
+                Ok(class) => Node::Class(class),
+                Err(c) => self.literal(c),
+            }),
+            c @ ('*' | '+' | '?' | '{') => Err(self.error(&format!("nothing to repeat before '{}'", c))),
+            c @ ('^' | '$') => Err(self.error(&format!("'{}' is not supported, the pattern always matches the whole string", c))),
+            c => Ok(self.literal(c)),
+        }
+    }
+
+    // A literal is folded like the input when the case is ignored, e.g. 'ß' is "ss".
+    fn literal(&self, c: char) -> Node {
+        if !self.ignore_case {
+            return Node::Char(c);
+        }
+        let folded = default_case_fold_str(c.encode_utf8(&mut [0; 4]));
+        let mut chars: Vec<Node> = folded.chars().map(Node::Char).collect();
+        if chars.len() == 1 { chars.pop().unwrap() } else { Node::Concat(chars) }
+    }
+
+    // Parses an escape after '\', into a class or an escaped character.
+    fn parse_escape(&mut self) -> Result<Result<Class, char>, RegexError> {
+        let c = self.next().ok_or_else(|| self.error("incomplete escape"))?;
+        let escaped = match c {
+            'd' => Ok(Class::digit(false)),
+            'D' => Ok(Class::digit(true)),
+            'w' => Ok(Class::word(false)),
+            'W' => Ok(Class::word(true)),
+            's' => Ok(Class::space(false)),
+            'S' => Ok(Class::space(true)),
+            'n' => Err('\n'),
+            't' => Err('\t'),
+            'r' => Err('\r'),
+            c if c.is_ascii_punctuation() => Err(c),
+            c => {
+                return Err(self.error(&format!("unsupported escape '\\{}'", c)));
+            }
+        };
+        Ok(escaped)
+    }
+
+    fn parse_class(&mut self) -> Result<Node, RegexError> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.position += 1;
+        }
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.next().ok_or_else(|| self.error("missing ']'"))?;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let start = match c {
+                '\\' => match self.parse_escape()? {
+                    Ok(class) if !class.negated => {
+                        ranges.extend(class.ranges);
+                        continue;
+                    }
+                    Ok(_) => {
+                        return Err(self.error("a negated escape is not supported in a class"));
+                    }
+                    Err(c) => c,
+                },
+                c => c,
+            };
+            if self.peek() == Some('-') && self.chars.get(self.position + 1).is_some_and(|c| *c != ']') {
+                self.position += 1;
+                let end = match self.next().unwrap() {
+                    '\\' => match self.parse_escape()? {
+                        Err(c) => c,
+                        Ok(_) => {
+                            return Err(self.error("a class cannot end a range"));
+                        }
+                    },
+                    c => c,
+                };
+                if end < start {
+                    return Err(self.error("the range is out of order"));
+                }
+                ranges.push((start, end));
+            } else {
+                ranges.push((start, start));
+            }
+        }
+        Ok(Node::Class(Class { ranges, negated }))
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Result<Node, RegexError> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.position += 1;
+                let min = self.parse_count()?.ok_or_else(|| self.error("missing the repetition count"))?;
+                let max = if self.peek() == Some(',') {
+                    self.position += 1;
+                    self.parse_count()?
+                } else {
+                    Some(min)
+                };
+                if self.peek() != Some('}') {
+                    return Err(self.error("missing '}'"));
+                }
+                if max.is_some_and(|max| max < min) {
+                    return Err(self.error("the repetition is out of order"));
+                }
+                (min, max)
+            }
+            _ => {
+                return Ok(atom);
+            }
+        };
+        self.position += 1;
+        if matches!(self.peek(), Some('*' | '+' | '?' | '{')) {
+            return Err(self.error("nested quantifiers are not supported"));
+        }
+        Ok(Node::Repeat { node: Box::new(atom), min, max })
+    }
+
+    fn parse_count(&mut self) -> Result<Option<u32>, RegexError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Ok(None);
+        }
+        let digits: String = self.chars[start..self.position].iter().collect();
+        match digits.parse::<u32>() {
+            Ok(count) if count <= Regex::MAX_REPETITION => Ok(Some(count)),
+            _ => Err(self.error(&format!("the repetition count exceeds {}", Regex::MAX_REPETITION))),
+        }
+    }
+}
+
+// Appends the instructions of a node, e.g. "a?" is [Split(+1, +2), Char('a')].
+fn compile(node: &Node, program: &mut Vec<Inst>) {
+    // A bounded repetition expands into copies of its node, so the size is checked along the way
+    if program.len() > Regex::MAX_PROGRAM_SIZE {
+        return;
+    }
+    match node {
+        Node::Char(c) => program.push(Inst::Char(*c)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class(class) => program.push(Inst::Class(class.clone())),
+        Node::Concat(nodes) => nodes.iter().for_each(|node| compile(node, program)),
+        Node::Alternation(branches) => {
+            let mut jumps = Vec::new();
+            for (index, branch) in branches.iter().enumerate() {
+                if index + 1 < branches.len() {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(branch, program);
+                    jumps.push(program.len());
+                    program.push(Inst::Jump(0));
+                    program[split] = Inst::Split(split + 1, program.len());
+                } else {
+                    compile(branch, program);
+                }
+            }
+            let end = program.len();
+            jumps.into_iter().for_each(|jump| program[jump] = Inst::Jump(end));
+        }
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                compile(node, program);
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program);
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    for _ in *min..*max {
+                        let split = program.len();
+                        program.push(Inst::Split(split + 1, 0));
+                        compile(node, program);
+                        program[split] = Inst::Split(split + 1, program.len());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//
+// Integer-only Jaro-Winkler similarity of strings, for matching names across issuers.
+//
+// The similarity is an exact fraction of integers, so the `similar` operator gives the same result
+// on every host and zkvm, and its score is only truncated to `SCORE_SCALE` digits when it is output.
+// The Jaro similarity of two strings of lengths |a| and |b|, with m matching characters of which
+// k are out of order, is
+//
+//   jaro = (m / |a| + m / |b| + (m - k / 2) / m) / 3
+//
+// where a character matches an equal character of the other string at most max(|a|, |b|) / 2 - 1
+// positions away. When the Jaro similarity is above 0.7, the Winkler variant boosts the strings
+// that share a prefix of l characters, at most 4: jaro_winkler = jaro + l / 10 * (1 - jaro).
+//
+use std::cmp::Ordering;
+
+use crate::Decimal;
+
+// The number of fractional digits of an output score.
+pub const SCORE_SCALE: u32 = 4;
+
+const MAX_PREFIX: usize = 4;
+
+///
+/// A similarity as the exact fraction numerator / denominator, between 0 and 1.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Score {
+    numerator: u128,
+    denominator: u128,
+}
+
+impl Score {
+    // Converts the score into a decimal of `SCORE_SCALE` digits, truncated toward zero, e.g. 0.9611.
+    pub fn to_decimal(&self) -> Decimal {
+        let units = (self.numerator * 10u128.pow(SCORE_SCALE)) / self.denominator;
+        Decimal::new(units as i128, SCORE_SCALE).unwrap()
+    }
+
+    // Compares the exact score with a decimal, e.g. a threshold.
+    pub fn cmp_decimal(&self, decimal: &Decimal) -> Ordering {
+        let units = decimal.units();
+        if units < 0 {
+            return Ordering::Greater;
+        }
+        // numerator / denominator against units / 10^scale, without any division
+        let lhs = self.numerator.checked_mul(10u128.pow(decimal.scale()));
+        let rhs = (units as u128).checked_mul(self.denominator);
+        match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => lhs.cmp(&rhs),
+            // Only the strings of millions of characters overflow, which are no names
+            _ => Ordering::Less,
+        }
+    }
+}
+
+// Computes the Jaro-Winkler similarity of two strings. Two empty strings are equal, so their similarity is 1.
+pub fn jaro_winkler(a: &str, b: &str) -> Score {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return Score { numerator: 1, denominator: 1 };
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_matched = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, c) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        if let Some(j) = (start..end).find(|&j| !b_matched[j] && b[j] == *c) {
+            b_matched[j] = true;
+            a_matches.push(*c);
+        }
+    }
+    let m = a_matches.len() as u128;
+    if m == 0 {
+        return Score { numerator: 0, denominator: 1 };
+    }
+    let b_matches = b.iter().zip(&b_matched).filter(|(_, matched)| **matched).map(|(c, _)| c);
+    let k = a_matches
+        .iter()
+        .zip(b_matches)
+        .filter(|(a, b)| a != b)
+        .count() as u128;
+
+    // jaro = (2m·m·|b| + 2m·m·|a| + (2m - k)·|a|·|b|) / (6·m·|a|·|b|)
+    let (la, lb) = (a.len() as u128, b.len() as u128);
+    let numerator = 2 * m * m * lb + 2 * m * m * la + (2 * m - k) * la * lb;
+    let denominator = 6 * m * la * lb;
+
+    // jaro_winkler = (10·jaro + l·(1 - jaro)) / 10, when jaro > 0.7
+    if 10 * numerator <= 7 * denominator {
+        return Score { numerator, denominator };
+    }
+    let prefix = a
+        .iter()
+        .zip(&b)
+        .take(MAX_PREFIX)
+        .take_while(|(a, b)| a == b)
+        .count() as u128;
+    Score {
+        numerator: 10 * numerator + prefix * (denominator - numerator),
+        denominator: 10 * denominator,
+    }
+}
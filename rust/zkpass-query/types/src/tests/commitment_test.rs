@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod commitment_test {
+    use crate::{ commitment::{ blind, canonical, commit, is_hash, parse_salt, verify_opening, SaltError }, Decimal, Entry, Val };
+
+    const SALT: &str = "000102030405060708090a0b0c0d0e0f";
+
+    #[test]
+    fn commitment_commit_test() {
+        let salt = parse_salt(SALT).unwrap();
+        let commitment = commit(&salt, &Val::Str(String::from("DOC897923CP")));
+        assert!(commitment == "f96f100068fb396cb122d4fab1cec1efa3509beb2db8339693ccadaecc074efe");
+        assert!(verify_opening(&commitment, SALT, &Val::Str(String::from("DOC897923CP"))));
+        assert!(!verify_opening(&commitment, SALT, &Val::Str(String::from("DOC897923CQ"))));
+        assert!(!verify_opening(&commitment, "000102030405060708090a0b0c0d0e0e", &Val::Str(String::from("DOC897923CP"))));
+        assert!(!verify_opening(&commitment, "0001", &Val::Str(String::from("DOC897923CP"))));
+    }
+
+    #[test]
+    fn commitment_canonical_test() {
+        let val = Val::Object(
+            vec![
+                Entry { key: String::from("b"), val: Val::Str(String::from("x\"y")) },
+                Entry {
+                    key: String::from("a"),
+                    val: Val::Array(vec![Val::Int(1), Val::Decimal(Decimal::new(25, 1).unwrap()), Val::Null]),
+                }
+            ]
+        );
+        assert!(canonical(&val) == "{\"a\":[1,2.5,null],\"b\":\"x\\\"y\"}");
+        let salt = parse_salt(SALT).unwrap();
+        assert!(commit(&salt, &val) == "cb0d9d4596c43ec6174029eb6204cb21138b1f94005ad410a7caf94e8c52222c");
+    }
+
+    #[test]
+    fn commitment_parse_salt_test() {
+        assert!(parse_salt(SALT).is_ok_and(|salt| salt.len() == 16));
+        assert!(parse_salt("000102030405060708090a0b0c0d0e").unwrap_err() == SaltError::ShortSaltError);
+        assert!(parse_salt("000102030405060708090a0b0c0d0e0g").unwrap_err() == SaltError::MalformedSaltError);
+        assert!(parse_salt("000102030405060708090a0b0c0d0e0f0").unwrap_err() == SaltError::MalformedSaltError);
+    }
+
+    #[test]
+    fn commitment_blind_test() {
+        let hash = blind(SALT, &Val::Str(String::from("DOC897923CP"))).unwrap();
+        assert!(hash == "f96f100068fb396cb122d4fab1cec1efa3509beb2db8339693ccadaecc074efe");
+        assert!(is_hash(&hash));
+        assert!(!is_hash(&hash.to_uppercase()));
+        assert!(!is_hash(&hash[1..]));
+        assert!(blind("0001", &Val::Str(String::from("DOC897923CP"))).unwrap_err() == SaltError::ShortSaltError);
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod decimal_test {
+    use crate::{ decimal::DecimalError, Decimal };
+
+    fn decimal(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn decimal_parse_test() {
+        assert!(decimal("12.345") == Decimal::new(12345, 3).unwrap());
+        assert!(decimal("-0.05") == Decimal::new(-5, 2).unwrap());
+        assert!(decimal("1.50") == decimal("1.5"));
+        assert!(decimal("2.0") == Decimal::from_i64(2));
+        assert!(decimal("-0.0") == Decimal::from_i64(0));
+        assert!(decimal("1.5e-7") == Decimal::new(15, 8).unwrap());
+        assert!(decimal("2.5E3") == Decimal::from_i64(2500));
+
+        // The fractional digits beyond the maximum scale are truncated toward zero
+        assert!(decimal("0.1234567890123456789") == Decimal::new(123456789012345678, 18).unwrap());
+        assert!(decimal("-1e-19") == Decimal::from_i64(0));
+
+        for malformed in ["", "-", ".5", "5.", "1.2.3", "+1", "1,5", "0x10", "1e", "NaN"] {
+            assert!(malformed.parse::<Decimal>().is_err_and(|e| e == DecimalError::MalformedDecimalError));
+        }
+        assert!("1e40".parse::<Decimal>().is_err_and(|e| e == DecimalError::OverflowError));
+        assert!(Decimal::new(1, 19).is_err_and(|e| e == DecimalError::OverflowError));
+    }
+
+    #[test]
+    fn decimal_from_str_exact_test() {
+        assert!(Decimal::from_str_exact("12.50") == Ok(Decimal::new(125, 1).unwrap()));
+        assert!(Decimal::from_str_exact("0.1234567890123456780") == Ok(Decimal::new(123456789012345678, 18).unwrap()));
+        assert!(Decimal::from_str_exact("1e-18") == Ok(Decimal::new(1, 18).unwrap()));
+
+        // The fractional digits beyond the maximum scale are an error rather than truncated
+        assert!(Decimal::from_str_exact("0.1234567890123456789") == Err(DecimalError::OverflowError));
+        assert!(Decimal::from_str_exact("1e-19") == Err(DecimalError::OverflowError));
+    }
+
+    #[test]
+    fn decimal_deserialize_test() {
+        // A deserialized decimal is normalized, so it equals the same parsed decimal
+        let decimal: Decimal = serde_json::from_str(r#"{"units":250,"scale":2}"#).unwrap();
+        assert!(decimal == self::decimal("2.5"));
+        assert!(decimal.scale() == 1);
+
+        // A scale beyond the maximum scale is an error rather than a panic when compared
+        let err = serde_json::from_str::<Decimal>(r#"{"units":1,"scale":40}"#).unwrap_err();
+        assert!(err.to_string().contains("the decimal overflows"));
+    }
+
+    #[test]
+    fn decimal_display_test() {
+        assert!(decimal("12.345").to_string() == "12.345");
+        assert!(decimal("-0.05").to_string() == "-0.05");
+        assert!(decimal("100").to_string() == "100");
+        assert!(decimal("1.5e-7").to_string() == "0.00000015");
+    }
+
+    #[test]
+    fn decimal_arithmetic_test() {
+        assert!(decimal("0.1").checked_add(&decimal("0.2")) == Some(decimal("0.3")));
+        assert!(decimal("1.25").checked_sub(&decimal("3")) == Some(decimal("-1.75")));
+        assert!(decimal("1.5").checked_mul(&decimal("-0.2")) == Some(decimal("-0.3")));
+        assert!(decimal("1").checked_div(&decimal("4")) == Some(decimal("0.25")));
+        assert!(decimal("2").checked_div(&decimal("3")) == Some(decimal("0.666666666666666666")));
+        assert!(decimal("-2").checked_div(&decimal("3")) == Some(decimal("-0.666666666666666666")));
+        assert!(decimal("5.5").checked_rem(&decimal("2")) == Some(decimal("1.5")));
+        assert!(decimal("-5.5").checked_rem(&decimal("2")) == Some(decimal("-1.5")));
+
+        // Products beyond the maximum scale are truncated toward zero
+        let small = decimal("0.000000001");
+        assert!(small.checked_mul(&small).is_some_and(|v| v == decimal("0.000000000000000001")));
+        assert!(small.checked_mul(&decimal("0.1").checked_mul(&small).unwrap()) == Some(Decimal::from_i64(0)));
+
+        assert!(decimal("1").checked_div(&Decimal::from_i64(0)).is_none());
+        assert!(decimal("1").checked_rem(&Decimal::from_i64(0)).is_none());
+        let huge = Decimal::new(i128::MAX, 0).unwrap();
+        assert!(huge.checked_add(&decimal("1")).is_none());
+        assert!(huge.checked_mul(&decimal("1.5")).is_none());
+    }
+
+    #[test]
+    fn decimal_ordering_test() {
+        assert!(decimal("0.39") < decimal("0.4"));
+        assert!(decimal("-0.5") < decimal("-0.25"));
+        assert!(decimal("-1.5") < decimal("-1"));
+        assert!(decimal("2") > decimal("1.999999999999999999"));
+
+        // Comparing does not rescale, so it cannot overflow
+        let huge = Decimal::new(i128::MAX, 0).unwrap();
+        let tiny = Decimal::new(1, 18).unwrap();
+        assert!(huge > tiny);
+        assert!(Decimal::new(i128::MIN, 0).unwrap() < tiny);
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod error_test {
+    use crate::{ QueryEngineError, ZkPassQueryEngineError };
+
+    #[test]
+    #[cfg(feature = "preview")]
+    fn error_within_test() {
+        let err = QueryEngineError::new(ZkPassQueryEngineError::UnexpectedValueError, "bad value")
+            .within(&["a/b", "~=="])
+            .within(&["0", "output"]);
+        assert!(err.location.as_deref() == Some("/0/output/a~1b/~0=="));
+    }
+
+    #[test]
+    fn error_from_kind_test() {
+        let err = QueryEngineError::from(ZkPassQueryEngineError::CyclesLimitExceededError);
+        assert!(err.kind == ZkPassQueryEngineError::CyclesLimitExceededError);
+        assert!(err.location.is_none());
+        assert!(err.token.is_none());
+        assert!(err.message == "the cycles limit of the zkvm is exceeded");
+    }
+
+    #[test]
+    fn error_display_test() {
+        let err = QueryEngineError::new(ZkPassQueryEngineError::LocalVarParsingError, "the lvar 'x' is not assigned")
+            .at("/1/output/y/lvar")
+            .with_token("x");
+        assert!(
+            err.to_string() == "LocalVarParsingError at '/1/output/y/lvar': the lvar 'x' is not assigned"
+        );
+    }
+
+    #[test]
+    fn error_json_test() {
+        let err = QueryEngineError::new(ZkPassQueryEngineError::DataVarParsingError, "invalid dvar path")
+            .at("/0/output/a/dvar")
+            .with_token("items[?");
+
+        let json = err.to_json();
+        assert!(
+            json ==
+                "{\"kind\":\"DataVarParsingError\",\"location\":\"/0/output/a/dvar\",\"token\":\"items[?\",\"message\":\"invalid dvar path\"}"
+        );
+        assert!(QueryEngineError::from_json(&json).is_ok_and(|e| e == err));
+    }
+}
@@ -0,0 +1,1464 @@
+#[cfg(test)]
+mod evaluator_test {
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    use crate::{
+        commitment::blind,
+        evaluate_query,
+        Decimal,
+        OutputReader,
+        PreviewInput,
+        QueryEngineError,
+        DvrVerifyingKey,
+        ReferenceTime,
+        Val,
+        ZkPassQueryEngineError,
+    };
+
+    fn read_file(path: &str) -> String {
+        std::fs::read_to_string(format!("./../../test/data/{}", path)).unwrap()
+    }
+
+    fn single_input(user_data: &str, query: &str) -> PreviewInput {
+        PreviewInput::new(HashMap::from([(String::from(""), user_data.to_string())]), query.to_string())
+    }
+
+    fn verifying_key(name: &str) -> DvrVerifyingKey {
+        DvrVerifyingKey { x: format!("{}-x", name), y: format!("{}-y", name) }
+    }
+
+    fn evaluate(user_data: serde_json::Value, query: serde_json::Value) -> Result<OutputReader, QueryEngineError> {
+        let output = evaluate_query(&single_input(&user_data.to_string(), &query.to_string()))?;
+        Ok(OutputReader::from_json(&output).unwrap())
+    }
+
+    #[test]
+    fn evaluator_basic_dvr_test() {
+        let query = read_file("basic-dvr.json");
+
+        let output = evaluate_query(&single_input(&read_file("basic-data.json"), &query)).unwrap();
+        assert!(output == "{\"result\":true}");
+
+        let output = evaluate_query(&single_input(&read_file("basic-data-false.json"), &query)).unwrap();
+        assert!(output == "{\"result\":false}");
+    }
+
+    #[test]
+    fn evaluator_conditional_dvr_test() {
+        let query = read_file("bca-finance-conditional.json");
+        let user_data = read_file("ramana-profile.json");
+
+        let output = evaluate_query(&single_input(&user_data, &query)).unwrap();
+        let output_reader = OutputReader::from_json(&output).unwrap();
+        assert!(output_reader.find_bool("result").is_some_and(|v| v));
+        assert!(output_reader.find_string("email").is_some_and(|v| v == "Ramana.Maharshi@karma.org"));
+        assert!(output_reader.find_i64("credit").is_some_and(|v| v == 755));
+        assert!(output_reader.find("name").is_none());
+    }
+
+    #[test]
+    fn evaluator_employee_onboarding_dvr_test() {
+        let query = read_file("employee-onboarding-dvr.json");
+
+        let output = evaluate_query(&single_input(&read_file("jane-blood-test-result.json"), &query)).unwrap();
+        let output_reader = OutputReader::from_json(&output).unwrap();
+        assert!(output_reader.find_bool("result").is_some_and(|v| v));
+        assert!(output_reader.find_string("name").is_some_and(|v| v == "Jane"));
+
+        let output = evaluate_query(&single_input(&read_file("jane2-blood-test-result.json"), &query)).unwrap();
+        let output_reader = OutputReader::from_json(&output).unwrap();
+        assert!(output_reader.find_bool("result").is_some_and(|v| !v));
+    }
+
+    #[test]
+    fn evaluator_proof_method_input_test() {
+        // The proof method input only holds the user data and the query
+        #[derive(serde::Serialize)]
+        struct ProofMethodInput {
+            user_data: HashMap<String, String>,
+            query: String,
+        }
+
+        let input = ProofMethodInput {
+            user_data: HashMap::from([(String::from(""), read_file("basic-data.json"))]),
+            query: read_file("basic-dvr.json"),
+        };
+        let preview = PreviewInput::from_proof_method_input(&input).unwrap();
+        assert!(preview.user_data == input.user_data && preview.query == input.query);
+        assert!(preview.reference_time.is_none() && preview.salts.is_empty() && preview.dvr_verifying_key.is_none());
+
+        let output = evaluate_query(&preview).unwrap();
+        let output_reader = OutputReader::from_json(&output).unwrap();
+        assert!(output_reader.find_bool("result").is_some_and(|v| v));
+    }
+
+    #[test]
+    fn evaluator_multiple_user_data_test() {
+        let input = PreviewInput {
+            user_data: HashMap::from([
+                (String::from("bank"), read_file("multiple/bank.json")),
+                (String::from("health"), read_file("multiple/health.json")),
+            ]),
+            query: read_file("multiple/insurance-dvr.json"),
+            reference_time: None,
+            salts: HashMap::new(),
+            dvr_verifying_key: None,
+        };
+
+        let output = evaluate_query(&input).unwrap();
+        let output_reader = OutputReader::from_json(&output).unwrap();
+        assert!(output_reader.find_bool("result").is_some_and(|v| v));
+        assert!(output_reader.find_string("name").is_some_and(|v| v == "Dewi"));
+    }
+
+    #[test]
+    fn evaluator_array_set_operators_test() {
+        let query =
+            json!([
+                {
+                    "output": {
+                        "same_license": {
+                            "intersects": [
+                                [{ "dvar": "bank.personalInfo.driverLicenseNumber" }],
+                                [{ "dvar": "health.personalInfo.driverLicenseNumber" }, { "dvar": "health.customerID" }]
+                            ]
+                        }
+                    }
+                },
+                {
+                    "output": {
+                        "known_allergies": {
+                            "subset_of": [{ "dvar": "health.recentHealthRecords.allergies" }, ["Dust", "Latex", "Peanuts", "Pollen"]]
+                        }
+                    }
+                },
+                { "output": { "has_pollen": { "superset_of": [{ "dvar": "health.recentHealthRecords.allergies" }, ["Pollen"]] } } },
+                { "output": { "no_overlap": { "intersects": [{ "dvar": "health.recentHealthRecords.allergies" }, ["Latex"]] } } },
+                { "output": { "allergy_count": { "length": [{ "dvar": "health.recentHealthRecords.allergies" }] } } },
+                {
+                    "output": {
+                        "daily": { "==": [{ "dvar": "health.recentHealthRecords.medications[].frequency" }, ["daily", "daily"]] }
+                    }
+                },
+                { "output": { "numbers": { "==": [[1, 2.0, [3]], [1.0, 2, [3.0]]] } } },
+                { "output": { "order": { "==": [["a", "b"], ["b", "a"]] } } }
+            ]);
+        let input = PreviewInput {
+            user_data: HashMap::from([
+                (String::from("bank"), read_file("multiple/bank.json")),
+                (String::from("health"), read_file("multiple/health.json")),
+            ]),
+            query: query.to_string(),
+            reference_time: None,
+            salts: HashMap::new(),
+            dvr_verifying_key: None,
+        };
+
+        let output = evaluate_query(&input).unwrap();
+        let output_reader = OutputReader::from_json(&output).unwrap();
+        assert!(output_reader.find_bool("same_license").is_some_and(|v| v));
+        assert!(output_reader.find_bool("known_allergies").is_some_and(|v| v));
+        assert!(output_reader.find_bool("has_pollen").is_some_and(|v| v));
+        assert!(output_reader.find_bool("no_overlap").is_some_and(|v| !v));
+        assert!(output_reader.find_i64("allergy_count").is_some_and(|v| v == 3));
+        assert!(output_reader.find_bool("daily").is_some_and(|v| v));
+        assert!(output_reader.find_bool("numbers").is_some_and(|v| v));
+        assert!(output_reader.find_bool("order").is_some_and(|v| !v));
+    }
+
+    #[test]
+    fn evaluator_array_set_operators_errors_test() {
+        let user_data = json!({ "skills": ["Rust", "SQL"], "name": "Jane" });
+
+        let query = json!([{ "output": { "a": { "subset_of": [{ "dvar": "skills" }, { "dvar": "name" }] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/subset_of"));
+
+        let query = json!([{ "output": { "a": { "length": [true] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+
+        let query = json!([{ "output": { "a": { "length": [{ "dvar": "skills" }, 1] } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedOperatorParsingError);
+    }
+
+    #[test]
+    fn evaluator_array_dvr_test() {
+        let query = read_file("array-dvr.json");
+
+        let output = evaluate_query(&single_input(&read_file("array-user-data.json"), &query)).unwrap();
+        let output_reader = OutputReader::from_json(&output).unwrap();
+        assert!(output_reader.find_bool("result").is_some_and(|v| !v));
+
+        let names = output_reader.find("names").unwrap();
+        assert!(
+            *names ==
+                Val::Array(
+                    vec![
+                        Val::Str(String::from("John")),
+                        Val::Str(String::from("Jane")),
+                        Val::Str(String::from("Dewi")),
+                        Val::Str(String::from("ramana"))
+                    ]
+                )
+        );
+    }
+
+    #[test]
+    fn evaluator_array_quantifier_test() {
+        let user_data = json!({ "items": [{ "age": 30 }, { "age": 45 }] });
+        let query =
+            json!([
+                { "output": { "any": { ">=": [{ "dvar": "items[].age" }, 40, "or"] } } },
+                { "output": { "all": { ">=": [{ "dvar": "items[].age" }, 40, "and"] } } },
+                { "output": { "default": { "<": [{ "dvar": "items[].age" }, 50] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_bool("any").is_some_and(|v| v));
+        assert!(output_reader.find_bool("all").is_some_and(|v| !v));
+        assert!(output_reader.find_bool("default").is_some_and(|v| v));
+    }
+
+    #[test]
+    fn evaluator_get_age_test() {
+        let user_data = json!({ "dateOfBirth": "01/01/1900" });
+        let query =
+            json!([
+                { "output": { "result": { ">=": [{ "get_age": [{ "dvar": "dateOfBirth" }, "DD/MM/YYYY"] }, 100] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_bool("result").is_some_and(|v| v));
+    }
+
+    #[test]
+    fn evaluator_arithmetic_test() {
+        let user_data = json!({ "accounts": [{ "balance": 1200 }, { "balance": 800 }], "debt": 300, "income": 1000 });
+        let query =
+            json!([
+                { "assign": { "total": { "+": [{ "dvar": "accounts[0].balance" }, { "dvar": "accounts[1].balance" }] } } },
+                { "output": { "total": { "lvar": "total" } } },
+                { "output": { "eligible": { ">=": [{ "lvar": "total" }, 1500] } } },
+                { "output": { "ratio_ok": { "<": [{ "*": [{ "dvar": "debt" }, 10] }, { "*": [{ "dvar": "income" }, 4] }] } } },
+                { "output": { "difference": { "-": [{ "dvar": "debt" }, { "dvar": "income" }] } } },
+                { "output": { "quotient": { "/": [-7, 2] } } },
+                { "output": { "remainder": { "%": [-7, 2] } } },
+                { "output": { "product": { "*": [2, 3, 4] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_i64("total").is_some_and(|v| v == 2000));
+        assert!(output_reader.find_bool("eligible").is_some_and(|v| v));
+        assert!(output_reader.find_bool("ratio_ok").is_some_and(|v| v));
+        assert!(output_reader.find_i64("difference").is_some_and(|v| v == -700));
+        assert!(output_reader.find_i64("quotient").is_some_and(|v| v == -3));
+        assert!(output_reader.find_i64("remainder").is_some_and(|v| v == -1));
+        assert!(output_reader.find_i64("product").is_some_and(|v| v == 24));
+    }
+
+    #[test]
+    fn evaluator_arithmetic_errors_test() {
+        let user_data = json!({ "name": "Jane" });
+        let cases = vec![
+            (json!([{ "output": { "a": { "+": [i64::MAX, 1] } } }]), ZkPassQueryEngineError::ArithmeticOverflowError),
+            (json!([{ "output": { "a": { "-": [i64::MIN, 1] } } }]), ZkPassQueryEngineError::ArithmeticOverflowError),
+            (json!([{ "output": { "a": { "*": [i64::MAX, 2] } } }]), ZkPassQueryEngineError::ArithmeticOverflowError),
+            (json!([{ "output": { "a": { "/": [i64::MIN, -1] } } }]), ZkPassQueryEngineError::ArithmeticOverflowError),
+            (json!([{ "output": { "a": { "/": [1, 0] } } }]), ZkPassQueryEngineError::DivisionByZeroError),
+            (json!([{ "output": { "a": { "%": [1, 0] } } }]), ZkPassQueryEngineError::DivisionByZeroError),
+            (json!([{ "output": { "a": { "+": [{ "dvar": "name" }, 1] } } }]), ZkPassQueryEngineError::UnexpectedValueError),
+            (json!([{ "output": { "a": { "-": [1, 2, 3] } } }]), ZkPassQueryEngineError::UnexpectedOperatorParsingError),
+            (json!([{ "output": { "a": { "+": [1] } } }]), ZkPassQueryEngineError::ExpectingSecondOperandParsingError),
+        ];
+
+        for (query, expected_error) in cases {
+            let result = evaluate(user_data.clone(), query);
+            assert!(result.is_err_and(|e| e.kind == expected_error));
+        }
+
+        let err = evaluate(user_data, json!([{ "assign": { "a": { "+": [1, { "*": [i64::MAX, 2] }] } } }])).err().unwrap();
+        assert!(err.location.as_deref() == Some("/0/assign/a/+/1/*"));
+        assert!(err.token.as_deref() == Some("*"));
+    }
+
+    #[test]
+    fn evaluator_decimal_test() {
+        let user_data =
+            json!({
+                "debt": 1250.5,
+                "income": 3500,
+                "rate": 0.035,
+                "vitaminD": [21.7, 35.2],
+                "principal": 10000
+            });
+        let query =
+            json!([
+                { "output": { "ratio_ok": { "<": [{ "/": [{ "dvar": "debt" }, { "dvar": "income" }] }, 0.4] } } },
+                { "output": { "interest": { "*": [{ "dvar": "principal" }, { "dvar": "rate" }] } } },
+                { "output": { "total": { "+": [0.1, 0.2] } } },
+                { "output": { "deficient": { "<": [{ "dvar": "vitaminD" }, 30, "or"] } } },
+                { "output": { "exact": { "==": [{ "dvar": "rate" }, 0.0350] } } },
+                { "output": { "rate": { "dvar": "rate" } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_bool("ratio_ok").is_some_and(|v| v));
+        assert!(output_reader.find_decimal("interest").is_some_and(|v| v == Decimal::from_i64(350)));
+        assert!(output_reader.find_decimal("total").is_some_and(|v| v.to_string() == "0.3"));
+        assert!(output_reader.find_bool("deficient").is_some_and(|v| v));
+        assert!(output_reader.find_bool("exact").is_some_and(|v| v));
+        assert!(output_reader.find_decimal("rate").is_some_and(|v| v == Decimal::new(35, 3).unwrap()));
+
+        let query = json!([{ "output": { "third": { "/": [10, 3.0] } } }]);
+        let output_reader = evaluate(json!({}), query).unwrap();
+        assert!(output_reader.find_decimal("third").is_some_and(|v| v.to_string() == "3.333333333333333333"));
+
+        let query = json!([{ "output": { "a": { "/": [1.5, 0] } } }]);
+        let result = evaluate(json!({}), query);
+        assert!(result.is_err_and(|e| e.kind == ZkPassQueryEngineError::DivisionByZeroError));
+    }
+
+    #[test]
+    fn evaluator_date_operators_test() {
+        let user_data = json!({ "dateOfBirth": "21/04/2003", "issued": "01/15/2024", "expiry": "01/15/2029" });
+        let query =
+            json!([
+                { "output": { "adult": { ">=": [{ "age_years": [{ "dvar": "dateOfBirth" }, "DD/MM/YYYY"] }, 18] } } },
+                { "output": { "validity": { "days_between": [{ "dvar": "issued" }, { "dvar": "expiry" }, "MM/DD/YYYY"] } } },
+                { "output": { "backwards": { "days_between": ["15/03/2024", "01/03/2024", "DD/MM/YYYY"] } } },
+                { "output": { "before": { "date_before": [{ "dvar": "issued" }, { "dvar": "expiry" }, "MM/DD/YYYY"] } } },
+                { "output": { "after": { "date_after": [{ "dvar": "issued" }, { "dvar": "expiry" }, "MM/DD/YYYY"] } } },
+                { "output": { "same_day": { "date_before": ["01.02.2000", "01-02-2000", "DD/MM/YYYY"] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_bool("adult").is_some_and(|v| v));
+        assert!(output_reader.find_i64("validity").is_some_and(|v| v == 5 * 365 + 2));
+        assert!(output_reader.find_i64("backwards").is_some_and(|v| v == -14));
+        assert!(output_reader.find_bool("before").is_some_and(|v| v));
+        assert!(output_reader.find_bool("after").is_some_and(|v| !v));
+        assert!(output_reader.find_bool("same_day").is_some_and(|v| !v));
+    }
+
+    #[test]
+    fn evaluator_date_time_operators_test() {
+        let user_data = json!({ "issuedAt": "2024-03-14T20:00:00Z", "dateOfBirth": "1985-12-12" });
+        let query =
+            json!([
+                { "output": { "fresh": { "<": [{ "hours_since": [{ "dvar": "issuedAt" }, "RFC3339"] }, 24] } } },
+                { "output": { "hours": { "hours_since": [{ "dvar": "issuedAt" }, "RFC3339"] } } },
+                { "output": { "window": { "hours_between": ["2024-03-15T08:00:00", "2024-03-15T17:59:59", "YYYY-MM-DDThh:mm:ss"] } } },
+                { "output": { "age": { "age_years": [{ "dvar": "dateOfBirth" }, "YYYY-MM-DD"] } } },
+                { "output": { "before": { "date_before": [{ "dvar": "issuedAt" }, "2024-03-15T03:00:01+07:00", "RFC3339"] } } }
+            ]);
+
+        let mut input = single_input(&user_data.to_string(), &query.to_string());
+        input.reference_time = Some(ReferenceTime { timestamp: 1_710_500_400, utc_offset: 7 * 3600 });
+        let output_reader = OutputReader::from_json(&evaluate_query(&input).unwrap()).unwrap();
+        assert!(output_reader.find_bool("fresh").is_some_and(|v| v));
+        assert!(output_reader.find_i64("hours").is_some_and(|v| v == 15));
+        assert!(output_reader.find_i64("window").is_some_and(|v| v == 9));
+        assert!(output_reader.find_i64("age").is_some_and(|v| v == 38));
+        assert!(output_reader.find_bool("before").is_some_and(|v| v));
+    }
+
+    #[test]
+    fn evaluator_date_operators_errors_test() {
+        let user_data = json!({ "dateOfBirth": "21/04/2003", "age": 20 });
+        let cases = vec![
+            (json!([{ "output": { "a": { "age_years": [{ "dvar": "dateOfBirth" }, "YYYY/DD/MM"] } } }]), "/0/output/a/age_years/1"),
+            (json!([{ "output": { "a": { "age_years": [{ "dvar": "dateOfBirth" }, "MM/DD/YYYY"] } } }]), "/0/output/a/age_years"),
+            (json!([{ "output": { "a": { "age_years": [{ "dvar": "age" }, "DD/MM/YYYY"] } } }]), "/0/output/a/age_years"),
+            (json!([{ "output": { "a": { "date_after": ["01/01/2000", "DD/MM/YYYY", 1] } } }]), "/0/output/a/date_after"),
+        ];
+
+        for (query, expected_location) in cases {
+            let err = evaluate(user_data.clone(), query).err().unwrap();
+            assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+            assert!(err.location.as_deref() == Some(expected_location));
+        }
+
+        let query = json!([{ "output": { "a": { "date_before": ["01/01/2000", "DD/MM/YYYY"] } } }]);
+        let result = evaluate(user_data, query);
+        assert!(result.is_err_and(|e| e.kind == ZkPassQueryEngineError::ExpectingThirdOperandParsingError));
+    }
+
+    #[test]
+    fn evaluator_reference_time_test() {
+        let user_data = json!({ "dateOfBirth": "15/03/2006" });
+        let query = json!([{ "output": { "age": { "age_years": [{ "dvar": "dateOfBirth" }, "DD/MM/YYYY"] } } }]);
+        let evaluate_at = |reference_time: ReferenceTime| {
+            let mut input = single_input(&user_data.to_string(), &query.to_string());
+            input.reference_time = Some(reference_time);
+            OutputReader::from_json(&evaluate_query(&input).unwrap()).unwrap()
+        };
+
+        // 2024-03-14T20:00:00Z is still March 14 in UTC, but already March 15 in UTC+07:00
+        let utc = ReferenceTime { timestamp: 1_710_446_400, utc_offset: 0 };
+        let output_reader = evaluate_at(utc);
+        assert!(output_reader.find_i64("age").is_some_and(|v| v == 17));
+        assert!(output_reader.reference_time().is_some_and(|v| v == utc));
+        assert!(output_reader.enumerate()[0].key == ReferenceTime::OUTPUT_NAME);
+        assert!(output_reader.find_string(ReferenceTime::OUTPUT_NAME).is_some_and(|v| v == "2024-03-14T20:00:00Z"));
+
+        let jakarta = ReferenceTime { timestamp: 1_710_446_400, utc_offset: 7 * 3600 };
+        let output_reader = evaluate_at(jakarta);
+        assert!(output_reader.find_i64("age").is_some_and(|v| v == 18));
+        assert!(output_reader.reference_time().is_some_and(|v| v == jakarta));
+        assert!(output_reader.find_string(ReferenceTime::OUTPUT_NAME).is_some_and(|v| v == "2024-03-15T03:00:00+07:00"));
+    }
+
+    #[test]
+    fn evaluator_reference_time_errors_test() {
+        let mut input = single_input("{}", "[]");
+        input.reference_time = Some(ReferenceTime { timestamp: 0, utc_offset: 86_400 });
+        assert!(evaluate_query(&input).is_err_and(|e| e.kind == ZkPassQueryEngineError::ReferenceTimeError));
+
+        // Without a reference time, nothing is committed
+        input.reference_time = None;
+        let output_reader = OutputReader::from_json(&evaluate_query(&input).unwrap()).unwrap();
+        assert!(output_reader.reference_time().is_none());
+
+        let query = json!([{ "output": { "_zkpass_reference_time_": "2000-01-01T00:00:00Z" } }]);
+        let err = evaluate(json!({}), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::OutputStatementParsingError);
+        assert!(err.location.as_deref() == Some("/0/output/_zkpass_reference_time_"));
+    }
+
+    #[test]
+    fn evaluator_output_object_test() {
+        let user_data =
+            json!({
+                "address": { "city": "Jakarta", "zip": null },
+                "items": [{ "name": "a", "age": 30, "secret": "x" }, { "name": "b", "age": 40, "secret": "y" }]
+            });
+        let query =
+            json!([
+                { "output": { "address": { "dvar": "address" } } },
+                { "output": { "items": { "dvar": "items[].{name: name, age: age}" } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_path("address.city").is_some_and(|v| *v == Val::Str(String::from("Jakarta"))));
+        assert!(output_reader.find_path("address.zip").is_some_and(|v| *v == Val::Null));
+        assert!(output_reader.find_path("items[1].age").is_some_and(|v| *v == Val::Int(40)));
+        assert!(output_reader.find_path("items[0].secret").is_none());
+    }
+
+    #[test]
+    fn evaluator_output_escaped_string_test() {
+        let user_data = json!({ "name": "quote\"back\\slash" });
+        let query = json!([{ "output": { "name": { "dvar": "name" } } }]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_string("name").is_some_and(|v| v == "quote\"back\\slash"));
+    }
+
+    #[test]
+    fn evaluator_errors_test() {
+        let user_data = json!({ "name": "Jane", "skills": ["Rust"], "tags": ["a"] });
+        let cases = vec![
+            (json!({}), ZkPassQueryEngineError::DvrNotAnArrayError),
+            (json!([{ "print": {} }]), ZkPassQueryEngineError::UnknownStmtKeywordParsingError),
+            (json!([{ "output": [] }]), ZkPassQueryEngineError::OutputStatementExpectingOperandInObjectParsingError),
+            (json!([{ "assign": { "a": true, "b": true } }]), ZkPassQueryEngineError::AssignmentStatementExpectingOneOperandParsingError),
+            (json!([{ "if": { "condition": true } }]), ZkPassQueryEngineError::IfStatementMissingThenBlockParsingError),
+            (json!([{ "if": { "then": [] } }]), ZkPassQueryEngineError::IfStatementMissingConditionParsingError),
+            (json!([{ "if": { "condition": true, "then": [], "otherwise": [] } }]), ZkPassQueryEngineError::IfStatementUnknownKeywordParsingError),
+            (json!([{ "output": { "a": { "lvar": "missing" } } }]), ZkPassQueryEngineError::LocalVarParsingError),
+            (json!([{ "output": { "a": { "dvar": "missing" } } }]), ZkPassQueryEngineError::DataVariableResolutionError),
+            (json!([{ "output": { "a": { "dvar": "_name" } } }]), ZkPassQueryEngineError::DataVarNameNotStartingWithAlphabetError),
+            (json!([{ "output": { "a": { "==": { "dvar": "name" } } } }]), ZkPassQueryEngineError::ExpectingOperandsInArrayParsingError),
+            (json!([{ "output": { "a": { "==": [{ "dvar": "name" }] } } }]), ZkPassQueryEngineError::ExpectingSecondOperandParsingError),
+            (json!([{ "output": { "a": { "==": [{ "dvar": "name" }, "Jane", "xor"] } } }]), ZkPassQueryEngineError::ExpectingThirdOperandParsingError),
+            (json!([{ "output": { "a": { "=~": [{ "dvar": "name" }, "Jane"] } } }]), ZkPassQueryEngineError::UnexpectedOperatorParsingError),
+            (json!([{ "output": { "a": { ">": [{ "dvar": "name" }, 1] } } }]), ZkPassQueryEngineError::UnexpectedValueError),
+            (json!([{ "output": { "a": { ">": [{ "dvar": "skills" }, { "dvar": "tags" }] } } }]), ZkPassQueryEngineError::ArrayComparisonNotSupportedError),
+        ];
+
+        for (query, expected_error) in cases {
+            let result = evaluate(user_data.clone(), query);
+            assert!(result.is_err_and(|e| e.kind == expected_error));
+        }
+    }
+
+    #[test]
+    fn evaluator_error_location_test() {
+        let user_data = json!({ "name": "Jane", "age": 30 });
+        let cases = vec![
+            (
+                json!([{ "output": { "a": { "and": [true, { "dvar": "missing" }] } } }]),
+                ZkPassQueryEngineError::DataVariableResolutionError,
+                "/0/output/a/and/1/dvar",
+                "missing",
+            ),
+            (
+                json!([{ "assign": { "x": 1 } }, { "output": { "a": { ">": [{ "dvar": "name" }, { "lvar": "x" }] } } }]),
+                ZkPassQueryEngineError::UnexpectedValueError,
+                "/1/output/a/>",
+                ">",
+            ),
+            (
+                json!([{ "if": { "condition": { "dvar": "age" }, "then": [] } }]),
+                ZkPassQueryEngineError::UnexpectedValueError,
+                "/0/if/condition",
+                "",
+            ),
+            (
+                json!([
+                    { "if": { "condition": false, "then": [], "else": [{ "output": { "a": { "get_age": ["x", "DD/MM/YYYY"] } } }] } }
+                ]),
+                ZkPassQueryEngineError::UnexpectedValueError,
+                "/0/if/else/0/output/a/get_age",
+                "get_age",
+            ),
+        ];
+
+        for (query, expected_error, expected_location, expected_token) in cases {
+            let err = evaluate(user_data.clone(), query).err().unwrap();
+            assert!(err.kind == expected_error);
+            assert!(err.location.as_deref() == Some(expected_location));
+            assert!(err.token.as_deref().unwrap_or("") == expected_token);
+            assert!(!err.message.is_empty());
+        }
+    }
+
+    #[test]
+    fn evaluator_dvar_expression_test() {
+        let user_data =
+            json!({
+                "accounts": { "savings": { "balance": 10 }, "checking": { "balance": 2.5 } },
+                "items": [{ "name": "a", "age": 30 }, { "name": "b", "age": 50 }, { "name": "c", "age": 45 }]
+            });
+        let query =
+            json!([
+                { "output": { "balances": { "dvar": "accounts.*.balance" } } },
+                { "output": { "older": { "dvar": "items[?age > `40`].name" } } },
+                { "output": { "count": { "dvar": "length(items[?age > `40`])" } } },
+                { "output": { "none": { "dvar": "length(items[?age > `99`])" } } },
+                { "output": { "oldest": { "dvar": "max_by(items, &age).name" } } },
+                { "output": { "names": { "dvar": "join(',', sort(items[].name))" } } },
+                { "output": { "adult": { ">=": [{ "dvar": "min(items[].age)" }, 18] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(
+            output_reader
+                .find("balances")
+                .is_some_and(|v| *v == Val::Array(vec![Val::Decimal(Decimal::new(25, 1).unwrap()), Val::Int(10)]))
+        );
+        assert!(
+            output_reader
+                .find("older")
+                .is_some_and(|v| *v == Val::Array(vec![Val::Str(String::from("b")), Val::Str(String::from("c"))]))
+        );
+        assert!(output_reader.find_i64("count").is_some_and(|v| v == 2));
+        assert!(output_reader.find_i64("none").is_some_and(|v| v == 0));
+        assert!(output_reader.find_string("oldest").is_some_and(|v| v == "b"));
+        assert!(output_reader.find_string("names").is_some_and(|v| v == "a,b,c"));
+        assert!(output_reader.find_bool("adult").is_some_and(|v| v));
+    }
+
+    #[test]
+    fn evaluator_dvar_not_found_test() {
+        let user_data = json!({ "accounts": { "savings": { "balance": 10 } }, "items": [{ "name": "a" }] });
+
+        let query = json!([{ "output": { "a": { "dvar": "accounts.checking.balance" } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::DataVariableResolutionError);
+        assert!(err.location.as_deref() == Some("/0/output/a/dvar"));
+        assert!(err.token.as_deref() == Some("accounts.checking.balance"));
+        assert!(err.message.ends_with("'accounts.checking' resolves to nothing"));
+
+        let query = json!([{ "output": { "a": { "dvar": "items[3].name" } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.message.ends_with("'items[3]' resolves to nothing"));
+
+        let query = json!([{ "output": { "a": { "dvar": "name" } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.message == "the dvar 'name' is not found in the user data");
+    }
+
+    #[test]
+    fn evaluator_array_operators_test() {
+        let user_data =
+            json!({
+                "transactions": [
+                    { "amount": 1500000, "date": "2024-03-01", "tags": ["salary"] },
+                    { "amount": 2000000.5, "date": "2024-02-10", "tags": [] },
+                    { "amount": 500000, "date": "2024-03-10", "tags": ["food"] },
+                    { "amount": 3000000, "date": "2023-10-01", "tags": ["car"] },
+                    { "amount": 1200000, "date": "2024-01-20", "tags": ["rent"] }
+                ],
+                "scores": [7, 3, 9]
+            });
+        let recent_large =
+            json!({
+                "and": [
+                    { ">": [{ "ivar": "amount" }, 1000000] },
+                    { "<=": [{ "days_between": [{ "ivar": "date" }, "2024-03-15", "YYYY-MM-DD"] }, 90] }
+                ]
+            });
+        let query =
+            json!([
+                { "output": { "eligible": { ">=": [{ "count": [{ "dvar": "transactions" }, recent_large] }, 3] } } },
+                { "output": { "large": { "map": [{ "filter": [{ "dvar": "transactions" }, recent_large] }, { "ivar": "date" }] } } },
+                { "output": { "total": { "sum": [{ "dvar": "transactions" }, { "ivar": "amount" }] } } },
+                { "output": { "score_total": { "sum": [{ "dvar": "scores" }] } } },
+                { "output": { "count": { "count": [{ "dvar": "scores" }] } } },
+                { "output": { "min": { "min": [{ "dvar": "scores" }] } } },
+                { "output": { "largest": { "max": [{ "dvar": "transactions" }, { "ivar": "amount" }] } } },
+                { "output": { "any_car": { "any": [{ "dvar": "transactions" }, { "==": [{ "ivar": "tags" }, "car", "or"] }] } } },
+                { "output": { "all_positive": { "all": [{ "dvar": "scores" }, { ">": [{ "ivar": "@" }, 0] }] } } },
+                { "output": { "none_tagged": { "none": [{ "dvar": "transactions" }, { "==": [{ "ivar": "tags" }, "x", "or"] }] } } },
+                { "output": { "empty_sum": { "sum": [[]] } } },
+                { "output": { "empty_all": { "all": [[], false] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_bool("eligible").is_some_and(|v| v));
+        assert!(
+            output_reader
+                .find("large")
+                .is_some_and(|v| {
+                    *v ==
+                        Val::Array(
+                            vec![
+                                Val::Str(String::from("2024-03-01")),
+                                Val::Str(String::from("2024-02-10")),
+                                Val::Str(String::from("2024-01-20"))
+                            ]
+                        )
+                })
+        );
+        assert!(output_reader.find_decimal("total").is_some_and(|v| v == Decimal::new(82000005, 1).unwrap()));
+        assert!(output_reader.find_i64("score_total").is_some_and(|v| v == 19));
+        assert!(output_reader.find_i64("count").is_some_and(|v| v == 3));
+        assert!(output_reader.find_i64("min").is_some_and(|v| v == 3));
+        assert!(output_reader.find_i64("largest").is_some_and(|v| v == 3000000));
+        assert!(output_reader.find_bool("any_car").is_some_and(|v| v));
+        assert!(output_reader.find_bool("all_positive").is_some_and(|v| v));
+        assert!(output_reader.find_bool("none_tagged").is_some_and(|v| v));
+        assert!(output_reader.find_i64("empty_sum").is_some_and(|v| v == 0));
+        assert!(output_reader.find_bool("empty_all").is_some_and(|v| v));
+    }
+
+    #[test]
+    fn evaluator_array_operators_nested_test() {
+        let user_data =
+            json!({
+                "accounts": [
+                    { "owner": "a", "transactions": [{ "amount": 10 }, { "amount": 20 }] },
+                    { "owner": "b", "transactions": [{ "amount": 5 }] }
+                ]
+            });
+        let query =
+            json!([
+                {
+                    "output": {
+                        "owners": {
+                            "map": [
+                                {
+                                    "filter": [
+                                        { "dvar": "accounts" },
+                                        { ">": [{ "sum": [{ "ivar": "transactions" }, { "ivar": "amount" }] }, 10] }
+                                    ]
+                                },
+                                { "ivar": "owner" }
+                            ]
+                        }
+                    }
+                }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find("owners").is_some_and(|v| *v == Val::Array(vec![Val::Str(String::from("a"))])));
+    }
+
+    #[test]
+    fn evaluator_array_operators_errors_test() {
+        let user_data = json!({ "items": [{ "age": 30 }, { "name": "b" }], "name": "a" });
+
+        let query = json!([{ "output": { "a": { "min": [[]] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/min"));
+
+        let query = json!([{ "output": { "a": { "count": [{ "dvar": "name" }] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/count/0"));
+
+        let query = json!([{ "output": { "a": { "filter": [[1, 2], { "ivar": "@" }] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/filter/1"));
+
+        let query = json!([{ "output": { "a": { "any": [{ "dvar": "items" }, { ">": [{ "ivar": "age" }, 18] }] } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::DataVariableResolutionError);
+        assert!(err.location.as_deref() == Some("/0/output/a/any/1/>/0/ivar"));
+        assert!(err.token.as_deref() == Some("age"));
+    }
+
+    #[test]
+    fn evaluator_string_operators_test() {
+        let user_data =
+            json!({
+                "email": "Jane.Doe@Company.com",
+                "phone": "+62-855-123-4567",
+                "license": "DL12345678",
+                "street": "Hauptstraße 1",
+                "emails": ["a@company.com", "b@company.com"]
+            });
+        let query =
+            json!([
+                { "output": { "company": { "ends_with": [{ "dvar": "email" }, "@Company.com"] } } },
+                { "output": { "company_exact": { "ends_with": [{ "dvar": "email" }, "@company.com"] } } },
+                { "output": { "company_folded": { "~ends_with": [{ "dvar": "email" }, "@COMPANY.COM"] } } },
+                { "output": { "indonesian": { "starts_with": [{ "dvar": "phone" }, "+62"] } } },
+                { "output": { "jane": { "~starts_with": [{ "dvar": "email" }, "JANE."] } } },
+                { "output": { "has_doe": { "contains": [{ "dvar": "email" }, "Doe"] } } },
+                { "output": { "has_strasse": { "~contains": [{ "dvar": "street" }, "STRASSE"] } } },
+                { "output": { "license": { "matches": [{ "dvar": "license" }, "[A-Z]{2}\\d{8}"] } } },
+                { "output": { "license_folded": { "~matches": [{ "dvar": "license" }, "dl\\d+"] } } },
+                { "output": { "all_company": { "ends_with": [{ "dvar": "emails" }, "@company.com"] } } },
+                { "output": { "length": { "length": [{ "dvar": "street" }] } } },
+                { "output": { "prefix": { "substring": [{ "dvar": "phone" }, 0, 3] } } },
+                { "output": { "domain": { "substring": [{ "dvar": "email" }, 9] } } },
+                { "output": { "beyond": { "substring": [{ "dvar": "email" }, 100, 5] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_bool("company").is_some_and(|v| v));
+        assert!(output_reader.find_bool("company_exact").is_some_and(|v| !v));
+        assert!(output_reader.find_bool("company_folded").is_some_and(|v| v));
+        assert!(output_reader.find_bool("indonesian").is_some_and(|v| v));
+        assert!(output_reader.find_bool("jane").is_some_and(|v| v));
+        assert!(output_reader.find_bool("has_doe").is_some_and(|v| v));
+        assert!(output_reader.find_bool("has_strasse").is_some_and(|v| !v));
+        assert!(output_reader.find_bool("license").is_some_and(|v| v));
+        assert!(output_reader.find_bool("license_folded").is_some_and(|v| v));
+        assert!(output_reader.find_bool("all_company").is_some_and(|v| v));
+        assert!(output_reader.find_i64("length").is_some_and(|v| v == 13));
+        assert!(output_reader.find_string("prefix").is_some_and(|v| v == "+62"));
+        assert!(output_reader.find_string("domain").is_some_and(|v| v == "Company.com"));
+        assert!(output_reader.find_string("beyond").is_some_and(|v| v.is_empty()));
+    }
+
+    #[test]
+    fn evaluator_contains_ignore_case_test() {
+        let query =
+            json!([
+                { "output": { "eszett": { "~contains": ["STRASSE", "ß"] } } },
+                { "output": { "final_sigma": { "~contains": ["ΟΔΟΣ", "ς"] } } },
+                { "output": { "sigma": { "~contains": ["ΟΔΟΣ", "σ"] } } },
+                { "output": { "upper": { "~contains": ["Hauptstraße 1", "STRAßE"] } } }
+            ]);
+        let outputs = |output_reader: OutputReader| {
+            ["eszett", "final_sigma", "sigma", "upper"].map(|name| output_reader.find_bool(name).unwrap())
+        };
+
+        let output_reader = evaluate(json!({}), query.clone()).unwrap();
+        assert!(outputs(output_reader) == [false, false, true, true]);
+
+        // The full case folding must be selected by a text statement
+        let mut folded_query = vec![json!({ "text": { "case_folding": "full" } })];
+        folded_query.extend(query.as_array().unwrap().iter().cloned());
+        let output_reader = evaluate(json!({}), json!(folded_query)).unwrap();
+        assert!(outputs(output_reader) == [true, true, true, true]);
+    }
+
+    #[test]
+    fn evaluator_string_operators_errors_test() {
+        let user_data = json!({ "name": "Jane", "age": 30, "pattern": "[a-" });
+
+        let query = json!([{ "output": { "a": { "starts_with": [{ "dvar": "age" }, "3"] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/starts_with"));
+
+        let query = json!([{ "output": { "a": { "matches": [{ "dvar": "name" }, { "dvar": "pattern" }] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.message.starts_with("invalid pattern '[a-'"));
+
+        let query = json!([{ "output": { "a": { "matches": [{ "dvar": "name" }, "(J"] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/matches/1"));
+
+        let query = json!([{ "output": { "a": { "substring": [{ "dvar": "name" }, -1] } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+    }
+
+    #[test]
+    fn evaluator_membership_operators_test() {
+        let mut countries: Vec<String> = (0..250u8).map(|i| format!("{}{}", (b'A' + i / 26) as char, (b'A' + i % 26) as char)).collect();
+        countries.push(String::from("ID"));
+        let user_data =
+            json!({
+                "country": "ID",
+                "hospitalId": "HOS00001",
+                "rating": 2.0,
+                "visited": ["AB", "ID"],
+                "approved": ["HOS00001", "HOS00002"]
+            });
+        let query =
+            json!([
+                { "const": { "countries": countries } },
+                { "const": { "ratings": [1, 2, 3] } },
+                { "output": { "allowed": { "in": [{ "dvar": "country" }, { "const": "countries" }] } } },
+                { "output": { "sanctioned": { "in": [{ "dvar": "country" }, ["KP", "IR"]] } } },
+                { "output": { "not_sanctioned": { "not_in": [{ "dvar": "country" }, ["KP", "IR"]] } } },
+                { "output": { "approved": { "in": [{ "dvar": "hospitalId" }, { "dvar": "approved" }] } } },
+                { "output": { "rated": { "in": [{ "dvar": "rating" }, { "const": "ratings" }] } } },
+                { "output": { "all_allowed": { "all": [{ "dvar": "visited" }, { "in": [{ "ivar": "@" }, { "const": "countries" }] }] } } },
+                { "output": { "ratings": { "const": "ratings" } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_bool("allowed").is_some_and(|v| v));
+        assert!(output_reader.find_bool("sanctioned").is_some_and(|v| !v));
+        assert!(output_reader.find_bool("not_sanctioned").is_some_and(|v| v));
+        assert!(output_reader.find_bool("approved").is_some_and(|v| v));
+        assert!(output_reader.find_bool("rated").is_some_and(|v| v));
+        assert!(output_reader.find_bool("all_allowed").is_some_and(|v| v));
+        assert!(output_reader.find("ratings").is_some_and(|v| *v == Val::Array(vec![Val::Int(1), Val::Int(2), Val::Int(3)])));
+    }
+
+    #[test]
+    fn evaluator_membership_operators_errors_test() {
+        let user_data = json!({ "address": { "city": "Jakarta" }, "country": "ID" });
+
+        let query = json!([{ "output": { "a": { "in": [{ "dvar": "address" }, ["Jakarta"]] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/in/0"));
+
+        let query = json!([{ "output": { "a": { "in": [{ "dvar": "country" }, { "dvar": "address" }] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/in/1"));
+
+        let query = json!([{ "output": { "a": { "in": [{ "dvar": "country" }, { "const": "countries" }] } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::ConstStatementParsingError);
+        assert!(err.location.as_deref() == Some("/0/output/a/in/1/const"));
+    }
+
+    #[test]
+    fn evaluator_optional_fields_test() {
+        let user_data =
+            json!({
+                "name": "Ramana",
+                "ratings": { "fico": 720, "creditKarma": null },
+                "loans": [null, 1200],
+                "accounts": [{ "id": "a", "closed": null }, { "id": "b" }, { "id": "c", "closed": "2023-01-01" }]
+            });
+        let query =
+            json!([
+                { "output": { "fico_exists": { "exists": [{ "dvar": "ratings.fico" }] } } },
+                { "output": { "credit_karma_exists": { "exists": [{ "dvar": "ratings.creditKarma" }] } } },
+                { "output": { "experian_exists": { "exists": [{ "dvar": "ratings.experian" }] } } },
+                { "output": { "fico_null": { "is_null": [{ "dvar": "ratings.fico" }] } } },
+                { "output": { "credit_karma_null": { "is_null": [{ "dvar": "ratings.creditKarma" }] } } },
+                { "output": { "experian_null": { "is_null": [{ "dvar": "ratings.experian" }] } } },
+                { "output": { "loan_exists": { "exists": [{ "dvar": "loans[0]" }] } } },
+                { "output": { "last_loan_exists": { "exists": [{ "dvar": "loans[-1]" }] } } },
+                { "output": { "other_loan_exists": { "exists": [{ "dvar": "loans[5]" }] } } },
+                { "output": { "name_exists": { "exists": [{ "dvar": "name" }] } } },
+                { "output": { "age_exists": { "exists": [{ "dvar": "age" }] } } },
+                {
+                    "output": {
+                        "qualified": {
+                            ">=": [{ "dvar": "ratings.experian", "default": 0 }, 700]
+                        }
+                    }
+                },
+                { "output": { "credit_karma": { "dvar": "ratings.creditKarma" } } },
+                { "output": { "credit_karma_or_zero": { "dvar": "ratings.creditKarma", "default": 0 } } },
+                { "output": { "loan": { "dvar": "loans[0]" } } },
+                { "output": { "fico": { "dvar": "ratings.fico", "default": 0 } } },
+                { "output": { "experian": { "dvar": "ratings.experian", "default": { "dvar": "ratings.fico" } } } },
+                { "output": { "closed": { "count": [{ "dvar": "accounts" }, { "exists": [{ "ivar": "closed" }] }] } } },
+                { "output": { "open": { "count": [{ "dvar": "accounts" }, { "is_null": [{ "ivar": "closed" }] }] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_bool("fico_exists").is_some_and(|v| v));
+        assert!(output_reader.find_bool("credit_karma_exists").is_some_and(|v| v));
+        assert!(output_reader.find_bool("experian_exists").is_some_and(|v| !v));
+        assert!(output_reader.find_bool("fico_null").is_some_and(|v| !v));
+        assert!(output_reader.find_bool("credit_karma_null").is_some_and(|v| v));
+        assert!(output_reader.find_bool("experian_null").is_some_and(|v| v));
+        assert!(output_reader.find_bool("loan_exists").is_some_and(|v| v));
+        assert!(output_reader.find_bool("last_loan_exists").is_some_and(|v| v));
+        assert!(output_reader.find_bool("other_loan_exists").is_some_and(|v| !v));
+        assert!(output_reader.find_bool("name_exists").is_some_and(|v| v));
+        assert!(output_reader.find_bool("age_exists").is_some_and(|v| !v));
+        assert!(output_reader.find_bool("qualified").is_some_and(|v| !v));
+        // A null dvar is a value, so only a missing one takes the default
+        assert!(output_reader.find("credit_karma").is_some_and(|v| *v == Val::Null));
+        assert!(output_reader.find("credit_karma_or_zero").is_some_and(|v| *v == Val::Null));
+        assert!(output_reader.find("loan").is_some_and(|v| *v == Val::Null));
+        assert!(output_reader.find_i64("fico").is_some_and(|v| v == 720));
+        assert!(output_reader.find_i64("experian").is_some_and(|v| v == 720));
+        assert!(output_reader.find_i64("closed").is_some_and(|v| v == 2));
+        assert!(output_reader.find_i64("open").is_some_and(|v| v == 2));
+    }
+
+    #[test]
+    fn evaluator_optional_fields_errors_test() {
+        let user_data = json!({ "ratings": { "fico": 720 } });
+
+        let query = json!([{ "output": { "a": { "dvar": "ratings.experian", "default": { "dvar": "ratings.equifax" } } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::DataVariableResolutionError);
+        assert!(err.location.as_deref() == Some("/0/output/a/default/dvar"));
+
+        // A null parent is not a null dvar, so its field is missing
+        let query = json!([{ "output": { "a": { "dvar": "ratings.experian.score" } } }]);
+        let err = evaluate(json!({ "ratings": { "experian": null } }), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::DataVariableResolutionError);
+
+        let query = json!([{ "output": { "a": { "exists": [{ "dvar": "ratings.fico" }, { "dvar": "ratings.fico" }] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedOperatorParsingError);
+
+        let query = json!([{ "output": { "a": { "is_null": [{ "lvar": "x" }] } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/is_null/0"));
+    }
+
+    #[test]
+    fn evaluator_switch_statement_test() {
+        let query =
+            json!([
+                {
+                    "switch": {
+                        "cases": [
+                            { "condition": { ">=": [{ "dvar": "pefindo.score" }, 700] }, "then": [{ "output": { "tier": "A" } }] },
+                            { "condition": { ">=": [{ "dvar": "pefindo.score" }, 600] }, "then": [{ "output": { "tier": "B" } }] },
+                            { "condition": { ">=": [{ "dvar": "pefindo.score" }, 500] }, "then": [{ "output": { "tier": "C" } }] }
+                        ],
+                        "default": [{ "output": { "tier": "D" } }]
+                    }
+                },
+                { "output": { "score": { "dvar": "pefindo.score" } } }
+            ]);
+
+        for (score, tier) in [(750, "A"), (700, "A"), (650, "B"), (500, "C"), (320, "D")] {
+            let user_data = json!({ "pefindo": { "score": score } });
+            let output_reader = evaluate(user_data, query.clone()).unwrap();
+            assert!(output_reader.find_string("tier").is_some_and(|v| v == tier));
+            assert!(output_reader.find_i64("score").is_some_and(|v| v == score));
+        }
+
+        // Without a default block, no statement runs when no case matches
+        let query =
+            json!([
+                { "output": { "tier": "none" } },
+                { "switch": { "cases": [{ "condition": false, "then": [{ "output": { "tier": "A" } }] }] } }
+            ]);
+        let output_reader = evaluate(json!({}), query).unwrap();
+        assert!(output_reader.find_string("tier").is_some_and(|v| v == "none"));
+    }
+
+    #[test]
+    fn evaluator_switch_statement_errors_test() {
+        let user_data = json!({ "pefindo": { "score": 650 } });
+
+        let query =
+            json!([
+                {
+                    "switch": {
+                        "cases": [
+                            { "condition": { ">=": [{ "dvar": "pefindo.score" }, 700] }, "then": [] },
+                            { "condition": { "dvar": "pefindo.score" }, "then": [] }
+                        ]
+                    }
+                }
+            ]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/switch/cases/1/condition"));
+
+        let query =
+            json!([
+                {
+                    "switch": {
+                        "cases": [{ "condition": false, "then": [] }],
+                        "default": [{ "output": { "a": { "dvar": "pefindo.grade" } } }]
+                    }
+                }
+            ]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::DataVariableResolutionError);
+        assert!(err.location.as_deref() == Some("/0/switch/default/0/output/a/dvar"));
+    }
+
+    #[test]
+    fn evaluator_define_dvr_test() {
+        let query = read_file("bca-finance-define.json");
+        let user_data = read_file("ramana-profile.json");
+
+        let output = evaluate_query(&single_input(&user_data, &query)).unwrap();
+        let output_reader = OutputReader::from_json(&output).unwrap();
+        assert!(output_reader.find_bool("result").is_some_and(|v| v));
+        assert!(output_reader.find_bool("other").is_some_and(|v| !v));
+    }
+
+    #[test]
+    fn evaluator_define_statement_test() {
+        let user_data = json!({ "loans": [{ "amount": 500 }, { "amount": 1500 }, { "amount": 2500 }], "limit": 1000 });
+        let query =
+            json!([
+                {
+                    "define": {
+                        "above": { "params": ["value", "threshold"], "body": { ">": [{ "param": "value" }, { "param": "threshold" }] } },
+                        "large_loans": {
+                            "params": ["threshold"],
+                            "body": {
+                                "count": [
+                                    { "dvar": "loans" },
+                                    { "ref": ["above", { "ivar": "amount" }, { "param": "threshold" }] }
+                                ]
+                            }
+                        },
+                        "limit": { "dvar": "limit" }
+                    }
+                },
+                { "output": { "large": { "ref": ["large_loans", { "ref": "limit" }] } } },
+                { "output": { "huge": { "ref": ["large_loans", { "*": [{ "ref": "limit" }, 2] }] } } },
+                { "output": { "any_large": { "any": [{ "dvar": "loans" }, { "ref": ["above", { "ivar": "amount" }, 2000] }] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_i64("large").is_some_and(|v| v == 2));
+        assert!(output_reader.find_i64("huge").is_some_and(|v| v == 1));
+        assert!(output_reader.find_bool("any_large").is_some_and(|v| v));
+    }
+
+    #[test]
+    fn evaluator_define_statement_errors_test() {
+        let user_data = json!({ "score": 700 });
+        let query =
+            json!([
+                {
+                    "define": {
+                        "rated": { "params": ["agency"], "body": { ">=": [{ "dvar": "ratings.fico" }, { "param": "agency" }] } }
+                    }
+                },
+                { "output": { "a": { "ref": ["rated", { "dvar": "score" }] } } }
+            ]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::DataVariableResolutionError);
+        assert!(err.location.as_deref() == Some("/1/output/a/ref"));
+        assert!(err.token.as_deref() == Some("ratings.fico"));
+        assert!(err.message.starts_with("the definition 'rated' fails at '/>=/0/dvar': "));
+
+        let query =
+            json!([
+                { "define": { "rated": { "params": ["agency"], "body": { ">=": [{ "dvar": "score" }, { "param": "agency" }] } } } },
+                { "output": { "a": { "ref": ["rated", { "dvar": "ratings.fico" }] } } }
+            ]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::DataVariableResolutionError);
+        assert!(err.location.as_deref() == Some("/1/output/a/ref/1/dvar"));
+    }
+
+    #[test]
+    fn evaluator_generalization_operators_test() {
+        let user_data = read_file("ramana-profile.json");
+        let query =
+            json!([
+                { "output": { "credit": { "bucket": [{ "dvar": "financialInfo.creditRatings.CreditKarma" }, [0, 600, 700, 800]] } } },
+                { "output": { "pefindo": { "bucket": [{ "dvar": "financialInfo.creditRatings.pefindo" }, [600, 700]] } } },
+                { "output": { "equifax": { "bucket": [{ "dvar": "financialInfo.creditRatings.EquiInfo" }, [700, 800]] } } },
+                {
+                    "output": {
+                        "grade": {
+                            "bucket": [{ "dvar": "financialInfo.creditRatings.pefindo" }, [600, 750.5], ["poor", "fair", "good"]]
+                        }
+                    }
+                },
+                { "output": { "balance": { "round_down": [{ "dvar": "financialInfo.accounts.savings.balance" }, 100000000] } } },
+                { "output": { "negative": { "round_down": [-15, 10] } } },
+                { "output": { "rate": { "round_down": [3.4567, 0.05] } } },
+                { "output": { "age": { "bucket": [{ "age_years": ["1985-12-12", "YYYY-MM-DD"] }, [18, 25, 35, 50, 65]] } } }
+            ]);
+
+        let mut input = single_input(&user_data, &query.to_string());
+        input.reference_time = Some(ReferenceTime { timestamp: 1_710_500_400, utc_offset: 7 * 3600 });
+        let output_reader = OutputReader::from_json(&evaluate_query(&input).unwrap()).unwrap();
+        assert!(output_reader.find_string("credit").is_some_and(|v| v == "700-800"));
+        assert!(output_reader.find_string("pefindo").is_some_and(|v| v == "700+"));
+        assert!(output_reader.find_string("equifax").is_some_and(|v| v == "<700"));
+        assert!(output_reader.find_string("grade").is_some_and(|v| v == "fair"));
+        assert!(output_reader.find_i64("balance").is_some_and(|v| v == 300000000));
+        assert!(output_reader.find_i64("negative").is_some_and(|v| v == -20));
+        assert!(output_reader.find_decimal("rate").is_some_and(|v| v == Decimal::new(345, 2).unwrap()));
+        assert!(output_reader.find_string("age").is_some_and(|v| v == "35-50"));
+    }
+
+    #[test]
+    fn evaluator_generalization_operators_errors_test() {
+        let user_data = json!({ "score": 700, "bounds": [800, 700], "name": "Ramana" });
+        let cases = [
+            (json!([{ "output": { "a": { "bucket": [{ "dvar": "score" }, { "dvar": "bounds" }] } } }]), "/0/output/a/bucket"),
+            (json!([{ "output": { "a": { "bucket": [{ "dvar": "name" }, [0, 100]] } } }]), "/0/output/a/bucket"),
+            (json!([{ "output": { "a": { "bucket": [{ "dvar": "score" }, [0], { "dvar": "name" }] } } }]), "/0/output/a/bucket"),
+            (json!([{ "output": { "a": { "round_down": [{ "dvar": "score" }, 0] } } }]), "/0/output/a/round_down"),
+            (json!([{ "output": { "a": { "round_down": [{ "dvar": "name" }, 10] } } }]), "/0/output/a/round_down"),
+            (json!([{ "output": { "a": { "bucket": [{ "dvar": "score" }, [0, 700, 700]] } } }]), "/0/output/a/bucket/1"),
+            (json!([{ "output": { "a": { "bucket": [{ "dvar": "score" }, [], []] } } }]), "/0/output/a/bucket/1"),
+            (json!([{ "output": { "a": { "bucket": [{ "dvar": "score" }, [0, "700"]] } } }]), "/0/output/a/bucket/1"),
+            (json!([{ "output": { "a": { "bucket": [{ "dvar": "score" }, [0, 700], ["low", "high"]] } } }]), "/0/output/a/bucket/2"),
+        ];
+        for (query, location) in cases {
+            let err = evaluate(user_data.clone(), query).err().unwrap();
+            assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+            assert!(err.location.as_deref() == Some(location));
+        }
+
+        let query = json!([{ "output": { "a": { "round_down": [-9223372036854775807i64, 10] } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::ArithmeticOverflowError);
+    }
+
+    #[test]
+    fn evaluator_commit_operator_test() {
+        let query =
+            json!([
+                { "output": { "result": { ">=": [{ "dvar": "financialInfo.creditRatings.pefindo" }, 650] } } },
+                { "output": { "doc": { "commit": [{ "dvar": "bcaDocID" }, "doc_salt"] } } },
+                { "output": { "address": { "commit": [{ "dvar": "contactInfo.address" }, "address_salt"] } } }
+            ]);
+        let mut input = single_input(&read_file("ramana-profile.json"), &query.to_string());
+        input.salts = HashMap::from([
+            (String::from("doc_salt"), String::from("000102030405060708090a0b0c0d0e0f")),
+            (String::from("address_salt"), String::from("0f0e0d0c0b0a09080706050403020100")),
+        ]);
+
+        let output_reader = OutputReader::from_json(&evaluate_query(&input).unwrap()).unwrap();
+        assert!(output_reader.find_bool("result").is_some_and(|v| v));
+        assert!(
+            output_reader
+                .find_string("doc")
+                .is_some_and(|v| v == "f96f100068fb396cb122d4fab1cec1efa3509beb2db8339693ccadaecc074efe")
+        );
+        let doc_id = Val::Str(String::from("DOC897923CP"));
+        assert!(output_reader.verify_opening("doc", "000102030405060708090a0b0c0d0e0f", &doc_id));
+        assert!(!output_reader.verify_opening("doc", "0f0e0d0c0b0a09080706050403020100", &doc_id));
+        assert!(!output_reader.verify_opening("result", "000102030405060708090a0b0c0d0e0f", &doc_id));
+
+        // The verifier reads the revealed address with its keys in any order
+        let address =
+            json!({
+                "zip": 12880,
+                "_zip_zkpass_public_": true,
+                "street": "1000 Komodo Street",
+                "city": "Jakarta"
+            });
+        let address = Val::from_value(address).unwrap();
+        assert!(output_reader.verify_opening("address", "0f0e0d0c0b0a09080706050403020100", &address));
+    }
+
+    #[test]
+    fn evaluator_commit_operator_errors_test() {
+        let user_data = json!({ "docId": "DOC897923CP" });
+        let salts = [
+            (HashMap::new(), "/0/output/a/commit"),
+            (HashMap::from([(String::from("salt"), String::from("0001"))]), "/0/output/a/commit"),
+            (HashMap::from([(String::from("salt"), String::from("not a hex salt"))]), "/0/output/a/commit"),
+        ];
+        for (salts, location) in salts {
+            let query = json!([{ "output": { "a": { "commit": [{ "dvar": "docId" }, "salt"] } } }]);
+            let mut input = single_input(&user_data.to_string(), &query.to_string());
+            input.salts = salts;
+            let err = evaluate_query(&input).err().unwrap();
+            assert!(err.kind == ZkPassQueryEngineError::SaltError);
+            assert!(err.location.as_deref() == Some(location));
+            assert!(err.token.as_deref() == Some("salt"));
+        }
+
+        let query = json!([{ "output": { "a": { "commit": [{ "dvar": "docId" }, { "dvar": "docId" }] } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::SaltError);
+        assert!(err.location.as_deref() == Some("/0/output/a/commit/1"));
+    }
+
+    #[test]
+    fn evaluator_pseudonym_operator_test() {
+        let query =
+            json!([
+                { "output": { "holder": { "pseudonym": [{ "dvar": "customerID" }] } } },
+                { "output": { "claim": { "pseudonym": [{ "dvar": "customerID" }, "promo-2024"] } } },
+                { "output": { "license": { "pseudonym": [{ "dvar": "personalInfo.driverLicenseNumber" }] } } }
+            ]);
+        let pseudonyms = |key: &DvrVerifyingKey| {
+            let mut input = single_input(&read_file("ramana-profile.json"), &query.to_string());
+            input.dvr_verifying_key = Some(key.clone());
+            OutputReader::from_json(&evaluate_query(&input).unwrap()).unwrap()
+        };
+
+        let bca_key = verifying_key("bca");
+        let bca = pseudonyms(&bca_key);
+        assert!(bca.is_scoped_to(&bca_key));
+        assert!(
+            bca.verifier_id().is_some_and(|v| v == "24cbc03932b5d8913a78523fe828473506b647509b4d442e1f27134085fc014b")
+        );
+        assert!(
+            bca
+                .find_string("holder")
+                .is_some_and(|v| v == "676297d687ef677d81e2325ab5f1fe634b696305a2e75fecc7a89abcd708abd2")
+        );
+        assert!(
+            bca
+                .find_string("claim")
+                .is_some_and(|v| v == "653820395ca67fb4930a73c21a924079fe7fd87d6dc0afe2ad5cbb05d2edfa94")
+        );
+        assert!(bca.find_string("license") != bca.find_string("holder"));
+        assert!(pseudonyms(&bca_key).find_string("holder") == bca.find_string("holder"));
+
+        let bank_key = verifying_key("bank");
+        let bank = pseudonyms(&bank_key);
+        assert!(bank.is_scoped_to(&bank_key));
+        assert!(bank.find_string("holder") != bca.find_string("holder"));
+        assert!(bank.find_string("license") != bca.find_string("license"));
+
+        // The pseudonyms derived for another verifier key are not scoped to the verifier
+        assert!(!bank.is_scoped_to(&bca_key));
+        assert!(!bca.is_scoped_to(&bank_key));
+    }
+
+    #[test]
+    fn evaluator_pseudonym_verifier_id_test() {
+        let query = json!([{ "output": { "holder": { "pseudonym": [{ "dvar": "customerID" }] } } }]);
+        let user_data = json!({ "customerID": "BCA123756108" });
+
+        // A verifier id supplied with the input is not a field of it, so it cannot scope the pseudonyms
+        let input = single_input(&user_data.to_string(), &query.to_string());
+        let mut input = serde_json::to_value(input).unwrap();
+        input["verifier_id"] = json!("did:example:bca");
+        let err = PreviewInput::from_proof_method_input(&input).unwrap_err();
+        assert!(err.kind == ZkPassQueryEngineError::SerializationError);
+        let err = evaluate_query(&single_input(&user_data.to_string(), &query.to_string())).unwrap_err();
+        assert!(err.kind == ZkPassQueryEngineError::VerifierIdentityError);
+
+        // A forged verifier id output does not match the verifier id of the DVR verifying key
+        let key = verifying_key("bca");
+        let output = json!({ "holder": "e36dd2ee", "_zkpass_verifier_id_": "did:example:bca" });
+        assert!(!OutputReader::from_json(&output.to_string()).unwrap().is_scoped_to(&key));
+    }
+
+    #[test]
+    fn evaluator_pseudonym_operator_errors_test() {
+        let user_data = json!({ "customerID": "BCA123756108" });
+
+        let query = json!([{ "output": { "a": { "pseudonym": [{ "dvar": "customerID" }] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::VerifierIdentityError);
+        assert!(err.location.as_deref() == Some("/0/output/a/pseudonym"));
+
+        let query = json!([{ "output": { "_zkpass_verifier_id_": "did:example:bca" } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::OutputStatementParsingError);
+        assert!(err.location.as_deref() == Some("/0/output/_zkpass_verifier_id_"));
+
+        let query = json!([{ "output": { "a": { "pseudonym": [{ "dvar": "customerID" }, 2024] } } }]);
+        let mut input = single_input(&user_data.to_string(), &query.to_string());
+        input.dvr_verifying_key = Some(verifying_key("bca"));
+        let err = evaluate_query(&input).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/pseudonym"));
+    }
+
+    #[test]
+    fn evaluator_mask_operator_test() {
+        let user_data =
+            json!({
+                "accountNumber": "1231774567",
+                "email": "dewi@yahoo.com",
+                "phone": "+62-856-685-0108",
+                "name": "Dewi Putri",
+                "pin": "1234"
+            });
+        let query =
+            json!([
+                { "output": { "accountNumber": { "mask": [{ "dvar": "accountNumber" }, 0, 4] } } },
+                { "output": { "email": { "mask": [{ "dvar": "email" }, 1, "@"] } } },
+                { "output": { "phone": { "mask": [{ "dvar": "phone" }, 3, 4, "#"] } } },
+                { "output": { "name": { "mask": [{ "dvar": "name" }, 1, "Putri"] } } },
+                { "output": { "no_delimiter": { "mask": [{ "dvar": "name" }, 1, "@"] } } },
+                { "output": { "pin": { "mask": [{ "dvar": "pin" }, 2, 2] } } },
+                { "output": { "unicode": { "mask": ["Straße", 1, 1, "•"] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_string("accountNumber").is_some_and(|v| v == "******4567"));
+        assert!(output_reader.find_string("email").is_some_and(|v| v == "d***@yahoo.com"));
+        assert!(output_reader.find_string("phone").is_some_and(|v| v == "+62#########0108"));
+        assert!(output_reader.find_string("name").is_some_and(|v| v == "D****Putri"));
+        assert!(output_reader.find_string("no_delimiter").is_some_and(|v| v == "D*********"));
+        assert!(output_reader.find_string("pin").is_some_and(|v| v == "****"));
+        assert!(output_reader.find_string("unicode").is_some_and(|v| v == "S••••e"));
+    }
+
+    #[test]
+    fn evaluator_mask_operator_errors_test() {
+        let user_data = json!({ "accountNumber": 1231774567i64, "email": "dewi@yahoo.com" });
+
+        let query = json!([{ "output": { "a": { "mask": [{ "dvar": "accountNumber" }, 0, 4] } } }]);
+        let err = evaluate(user_data.clone(), query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::MaskingError);
+        assert!(err.location.as_deref() == Some("/0/output/a/mask"));
+
+        let cases = [
+            json!([{ "output": { "a": { "mask": [{ "dvar": "email" }, -1, 4] } } }]),
+            json!([{ "output": { "a": { "mask": [{ "dvar": "email" }, 1, ""] } } }]),
+            json!([{ "output": { "a": { "mask": [{ "dvar": "email" }, 1, 4, "**"] } } }]),
+            json!([{ "output": { "a": { "mask": [{ "dvar": "email" }, 1, 4, 0] } } }]),
+        ];
+        for query in cases {
+            let err = evaluate(user_data.clone(), query).err().unwrap();
+            assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+            assert!(err.location.as_deref() == Some("/0/output/a/mask"));
+        }
+
+        let query = json!([{ "output": { "a": { "mask": [{ "dvar": "email" }, 1] } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::ExpectingThirdOperandParsingError);
+    }
+
+    #[test]
+    fn evaluator_blinded_in_operator_test() {
+        // The hashes of "DOC897923CP" and "DOC112233AB" with the salt, as made by the verifier
+        let salt = "000102030405060708090a0b0c0d0e0f";
+        let screened = [
+            "f96f100068fb396cb122d4fab1cec1efa3509beb2db8339693ccadaecc074efe",
+            &blind(salt, &Val::Str(String::from("DOC112233AB"))).unwrap(),
+        ];
+        let query =
+            json!([
+                { "output": { "listed": { "blinded_in": [{ "dvar": "bcaDocID" }, salt, screened] } } },
+                { "output": { "expected": { "blinded_in": [{ "dvar": "bcaDocID" }, salt, screened[0]] } } },
+                { "output": { "other": { "blinded_in": [{ "dvar": "bcaDocID" }, salt, screened[1]] } } },
+                { "output": { "resalted": { "blinded_in": [{ "dvar": "bcaDocID" }, "0f0e0d0c0b0a09080706050403020100", screened] } } }
+            ]);
+
+        let input = single_input(&read_file("ramana-profile.json"), &query.to_string());
+        let output_reader = OutputReader::from_json(&evaluate_query(&input).unwrap()).unwrap();
+        assert!(output_reader.find_bool("listed") == Some(true));
+        assert!(output_reader.find_bool("expected") == Some(true));
+        assert!(output_reader.find_bool("other") == Some(false));
+        assert!(output_reader.find_bool("resalted") == Some(false));
+    }
+
+    #[test]
+    fn evaluator_similarity_operators_test() {
+        let user_data =
+            json!({
+                "bank": { "firstName": "Muhammad  Rizky", "lastName": "Pratama" },
+                "health": { "firstName": "MUHAMAD RIZKI", "lastName": "Wijaya" }
+            });
+        let query =
+            json!([
+                { "output": { "score": { "similarity": [{ "dvar": "bank.firstName" }, { "dvar": "health.firstName" }] } } },
+                { "output": { "firstName": { "similar": [{ "dvar": "bank.firstName" }, { "dvar": "health.firstName" }, 0.9] } } },
+                { "output": { "lastName": { "similar": [{ "dvar": "bank.lastName" }, { "dvar": "health.lastName" }, 0.9] } } },
+                { "output": { "exact": { "similarity": ["Budi  Santoso", "BUDI SANTOSO"] } } }
+            ]);
+
+        let output_reader = evaluate(user_data, query).unwrap();
+        assert!(output_reader.find_decimal("score").is_some_and(|v| v == "0.956".parse::<Decimal>().unwrap()));
+        assert!(output_reader.find_bool("firstName") == Some(true));
+        assert!(output_reader.find_bool("lastName") == Some(false));
+        assert!(output_reader.find_decimal("exact") == Some(Decimal::from_i64(1)));
+    }
+
+    #[test]
+    fn evaluator_similarity_operators_errors_test() {
+        let user_data = json!({ "name": "Budi", "age": 30 });
+        let cases = [
+            json!([{ "output": { "a": { "similar": [{ "dvar": "name" }, { "dvar": "age" }, 0.9] } } }]),
+            json!([{ "output": { "a": { "similar": [{ "dvar": "name" }, "Budi", { "dvar": "name" }] } } }]),
+            json!([{ "output": { "a": { "similar": [{ "dvar": "name" }, "Budi", { "+": [1, 0.5] }] } } }]),
+        ];
+        for query in cases {
+            let err = evaluate(user_data.clone(), query).err().unwrap();
+            assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+            assert!(err.location.as_deref() == Some("/0/output/a/similar"));
+        }
+
+        let query = json!([{ "output": { "a": { "similar": [{ "dvar": "name" }, "Budi", 90] } } }]);
+        let err = evaluate(user_data, query).err().unwrap();
+        assert!(err.kind == ZkPassQueryEngineError::UnexpectedValueError);
+        assert!(err.location.as_deref() == Some("/0/output/a/similar/2"));
+    }
+
+    #[test]
+    fn evaluator_text_statement_test() {
+        // The name is in NFD, with a combining acute accent, and the city in upper case Turkish
+        let user_data = json!({ "name": "Jose\u{301} Garci\u{301}a", "city": "D\u{130}YARBAKIR", "company": "\u{fb01}nance" });
+        let comparisons =
+            json!([
+                { "output": { "name": { "==": [{ "dvar": "name" }, "Jos\u{e9} Garc\u{ed}a"] } } },
+                { "output": { "listed": { "in": [{ "dvar": "name" }, ["Jos\u{e9} Garc\u{ed}a"]] } } },
+                { "output": { "first_name": { "~starts_with": [{ "dvar": "name" }, "JOS\u{c9}"] } } },
+                { "output": { "city": { "~==": [{ "dvar": "city" }, "diyarbak\u{131}r"] } } },
+                { "output": { "company": { "==": [{ "dvar": "company" }, "finance"] } } }
+            ]);
+        let with_text = |text: serde_json::Value| {
+            let mut query = vec![json!({ "text": text })];
+            query.extend(comparisons.as_array().unwrap().iter().cloned());
+            evaluate(user_data.clone(), json!(query)).unwrap()
+        };
+        let outputs = |output_reader: OutputReader| {
+            ["name", "listed", "first_name", "city", "company"].map(|name| output_reader.find_bool(name).unwrap())
+        };
+
+        // Without a text statement, the strings are compared as they are
+        let output_reader = evaluate(user_data.clone(), comparisons.clone()).unwrap();
+        assert!(outputs(output_reader) == [false, false, false, false, false]);
+
+        assert!(outputs(with_text(json!({}))) == [false, false, false, false, false]);
+        assert!(outputs(with_text(json!({ "normalization": "nfc" }))) == [true, true, true, false, false]);
+        assert!(outputs(with_text(json!({ "normalization": "nfkc" }))) == [true, true, true, false, true]);
+        assert!(
+            outputs(with_text(json!({ "normalization": "nfkc", "case_folding": "turkic" }))) ==
+                [true, true, true, true, true]
+        );
+    }
+
+    #[test]
+    fn evaluator_user_data_error_test() {
+        let input = single_input("{", "[]");
+
+        let err = evaluate_query(&input).unwrap_err();
+        assert!(err.kind == ZkPassQueryEngineError::UserDataParsingError);
+        assert!(err.location.is_none());
+    }
+
+    #[test]
+    fn evaluator_user_data_inexact_number_test() {
+        let query = json!([{ "output": { "balance": { "dvar": "account.balance" } } }]);
+
+        let user_data = r#"{ "account": { "balance": 12345678901234567.89 } }"#;
+        let err = evaluate_query(&single_input(user_data, &query.to_string())).unwrap_err();
+        assert!(err.kind == ZkPassQueryEngineError::InexactNumberError);
+        assert!(err.message.contains("12345678901234567.89 at '/account/balance'"));
+
+        let user_data = r#"{ "account": { "balance": 12345678.90 } }"#;
+        let output = evaluate_query(&single_input(user_data, &query.to_string())).unwrap();
+        let output_reader = OutputReader::from_json(&output).unwrap();
+        assert!(output_reader.find_decimal("balance") == Some("12345678.9".parse().unwrap()));
+    }
+}
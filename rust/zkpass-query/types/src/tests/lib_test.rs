@@ -9,12 +9,16 @@ mod lib_test {
         lookup,
         Entry,
         LocalDate,
+        LocalDateError,
+        LocalDateTime,
         LookupTable,
         OutputReader,
         OutputReaderError,
         Val,
         ZkPassQueryEngineError,
     };
+    #[cfg(feature = "preview")]
+    use crate::Decimal;
 
     #[test]
     fn lib_zk_pass_query_engine_error_deserialize_test() {
@@ -95,15 +99,96 @@ mod lib_test {
     }
 
     #[test]
-    fn lib_output_reader_from_json_unsupported_type_error_test() {
-        let data = json!({
-            "some_array_key":{}
+    #[cfg(feature = "preview")]
+    fn lib_output_reader_find_decimal_test() {
+        let data = "{\"rate\":0.035,\"count\":3,\"values\":[1.5,2],\"name\":\"x\"}";
+
+        let output_reader = OutputReader::from_json(data).unwrap();
+        assert!(output_reader.find_decimal("rate").is_some_and(|v| v == Decimal::new(35, 3).unwrap()));
+        assert!(output_reader.find_decimal("count").is_some_and(|v| v == Decimal::from_i64(3)));
+        assert!(output_reader.find_decimal("name").is_none());
+        assert!(output_reader.find_i64("rate").is_none());
+        assert!(
+            output_reader.find("values").is_some_and(|v| {
+                *v == Val::Array(vec![Val::Decimal(Decimal::new(15, 1).unwrap()), Val::Int(2)])
+            })
+        );
+        assert!(Val::Decimal(Decimal::new(-35, 3).unwrap()).to_json() == "-0.035");
+    }
+
+    #[test]
+    #[cfg(feature = "preview")]
+    fn lib_output_reader_from_json_exact_decimal_test() {
+        let data =
+            "{\"third\":3.333333333333333333,\"big\":12345678901234567890,\"nested\":{\"a/b\":[0.1,-2.000000000000000001]},\"dup\":1.000000000000000001,\"dup\":2}";
+
+        let output_reader = OutputReader::from_json(data).unwrap();
+        assert!(output_reader.find_decimal("third").is_some_and(|v| v.to_string() == "3.333333333333333333"));
+        assert!(output_reader.find_decimal("big").is_some_and(|v| v.to_string() == "12345678901234567890"));
+        assert!(
+            output_reader.find("nested").is_some_and(|v| {
+                v.to_json() == "{\"a/b\":[0.1,-2.000000000000000001]}"
+            })
+        );
+        assert!(output_reader.find_i64("dup").is_some_and(|v| v == 2));
+
+        let output_reader = OutputReader::from_json("{\"tiny\":0.0000000000000000001}");
+        assert!(output_reader.is_err_and(|e| e == OutputReaderError::UnsupportedTypeError));
+    }
+
+    #[test]
+    #[cfg(feature = "preview")]
+    fn lib_output_reader_find_path_test() {
+        let data =
+            json!({
+                "address": { "city": "Jakarta", "geo": { "lat": -6.2 } },
+                "items": [{ "name": "a", "tags": [["x", "y"]] }, { "name": "b", "age": null }],
+                "nothing": null
         }).to_string();
 
-        let output_reader = OutputReader::from_json(&data);
+        let output_reader = OutputReader::from_json(&data).unwrap();
+        assert!(output_reader.find_path("address.city").is_some_and(|v| *v == Val::Str(String::from("Jakarta"))));
+        assert!(output_reader.find_path("address.geo.lat").is_some_and(|v| *v == Val::Decimal(Decimal::new(-62, 1).unwrap())));
+        assert!(output_reader.find_path("items[1].name").is_some_and(|v| *v == Val::Str(String::from("b"))));
+        assert!(output_reader.find_path("items[0].tags[0][1]").is_some_and(|v| *v == Val::Str(String::from("y"))));
+        assert!(output_reader.find_path("items[1].age").is_some_and(|v| *v == Val::Null));
+        assert!(output_reader.find("nothing").is_some_and(|v| *v == Val::Null));
+
+        for missing in ["address.zip", "items[2].name", "items.name", "address[0]", "items[x]", "items[0", "", ".city"] {
+            assert!(output_reader.find_path(missing).is_none(), "{}", missing);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "preview")]
+    fn lib_val_object_to_json_test() {
+        let val = Val::Object(vec![
+            Entry { key: String::from("na\"me"), val: Val::Str(String::from("Jane")) },
+            Entry { key: String::from("tags"), val: Val::Array(vec![Val::Null, Val::Object(vec![])]) },
+        ]);
+        assert!(val.to_json() == "{\"na\\\"me\":\"Jane\",\"tags\":[null,{}]}");
+
+        let round_trip = Val::from_value(serde_json::from_str(&val.to_json()).unwrap());
+        assert!(round_trip.is_ok_and(|v| v == val));
+    }
+
+    #[test]
+    fn lib_output_reader_from_json_unsupported_type_error_test() {
+        let data = "{\"some_number_key\":1e40}";
+
+        let output_reader = OutputReader::from_json(data);
         assert!(output_reader.is_err_and(|e| e == OutputReaderError::UnsupportedTypeError));
     }
 
+    #[test]
+    #[cfg(not(feature = "preview"))]
+    fn lib_output_reader_from_json_preview_types_error_test() {
+        for data in ["{\"some_object_key\":{}}", "{\"some_null_key\":null}", "{\"some_decimal_key\":0.5}"] {
+            let output_reader = OutputReader::from_json(data);
+            assert!(output_reader.is_err_and(|e| e == OutputReaderError::UnsupportedTypeError), "{}", data);
+        }
+    }
+
     #[test]
     fn lib_output_reader_from_json_expecting_object_error_test() {
         let data = json!([]).to_string();
@@ -217,10 +302,10 @@ mod lib_test {
             "`1234567890-=qwertyuiop[]asdfghjkl;'zxcvbnm,./~!@#$%^&*()_+QWERTYUIOP|ASDFGHJKL:ZXCVBNM<>?";
         let escaped_string = "\"\\\n\r\t\u{0008}\u{000C}abc";
 
-        let unchanged_string = escape_string(&normal_string);
+        let unchanged_string = escape_string(normal_string);
         assert!(unchanged_string == normal_string);
 
-        let escape_string = escape_string(&escaped_string);
+        let escape_string = escape_string(escaped_string);
         assert!(escape_string == "\\\"\\\\\\n\\r\\t\\b\\fabc");
     }
 
@@ -241,7 +326,7 @@ mod lib_test {
     fn lib_local_date_parse_date_invalid_format_error_test() {
         let invalid_date_string = "01/01";
         let format = date_format::DDMMYYYY;
-        let local_date = LocalDate::parse_date(&invalid_date_string, format);
+        let local_date = LocalDate::parse_date(invalid_date_string, format);
         assert!(local_date.is_none());
     }
 
@@ -250,7 +335,7 @@ mod lib_test {
         let invalid_date_string = "01,02,2003";
         let format = date_format::DDMMYYYY;
 
-        let local_date = LocalDate::parse_date(&invalid_date_string, &format);
+        let local_date = LocalDate::parse_date(invalid_date_string, format);
         assert!(local_date.is_none());
     }
 
@@ -260,10 +345,10 @@ mod lib_test {
         let first_format = date_format::DDMMYYYY;
         let second_format = date_format::MMDDYYYY;
 
-        let first_local_date = LocalDate::parse_date(&invalid_date_string, first_format);
+        let first_local_date = LocalDate::parse_date(invalid_date_string, first_format);
         assert!(first_local_date.is_none());
 
-        let second_local_date = LocalDate::parse_date(&invalid_date_string, second_format);
+        let second_local_date = LocalDate::parse_date(invalid_date_string, second_format);
         assert!(second_local_date.is_none());
     }
 
@@ -273,10 +358,10 @@ mod lib_test {
         let first_format = date_format::DDMMYYYY;
         let second_format = date_format::MMDDYYYY;
 
-        let first_local_date = LocalDate::parse_date(&invalid_date_string, first_format);
+        let first_local_date = LocalDate::parse_date(invalid_date_string, first_format);
         assert!(first_local_date.is_none());
 
-        let second_local_date = LocalDate::parse_date(&invalid_date_string, second_format);
+        let second_local_date = LocalDate::parse_date(invalid_date_string, second_format);
         assert!(second_local_date.is_none());
     }
 
@@ -286,10 +371,10 @@ mod lib_test {
         let first_format = date_format::DDMMYYYY;
         let second_format = date_format::MMDDYYYY;
 
-        let first_local_date = LocalDate::parse_date(&invalid_date_string, first_format);
+        let first_local_date = LocalDate::parse_date(invalid_date_string, first_format);
         assert!(first_local_date.is_none());
 
-        let second_local_date = LocalDate::parse_date(&invalid_date_string, second_format);
+        let second_local_date = LocalDate::parse_date(invalid_date_string, second_format);
         assert!(second_local_date.is_none());
     }
 
@@ -297,7 +382,7 @@ mod lib_test {
     fn lib_local_date_parse_date_format_invalid_format_error_test() {
         let date_string = "01/02/2003";
         let invalid_format = "DD/MM";
-        let local_date = LocalDate::parse_date(&date_string, invalid_format);
+        let local_date = LocalDate::parse_date(date_string, invalid_format);
         assert!(local_date.is_none());
     }
 
@@ -334,4 +419,97 @@ mod lib_test {
         assert_calculate_day_difference_result("20/02/2003", "02/02/2003", -18);
         assert_calculate_day_difference_result("02/02/2003", "02/02/2003", 0);
     }
+
+    fn assert_calculate_calendar_day_difference_result(
+        check_date: &str,
+        reference_date: &str,
+        expected_days: i64
+    ) {
+        let check_date = LocalDate::try_parse_date(check_date, date_format::DDMMYYYY).unwrap();
+        let reference_date = LocalDate::try_parse_date(reference_date, date_format::DDMMYYYY).unwrap();
+        let day_difference = LocalDate::calculate_calendar_day_difference(&check_date, &reference_date);
+        assert!(day_difference == expected_days, "{} {}", day_difference, expected_days);
+    }
+
+    #[test]
+    fn lib_local_date_calculate_calendar_day_difference_test() {
+        assert_calculate_calendar_day_difference_result("01/02/2000", "01/02/2003", 1096);
+        assert_calculate_calendar_day_difference_result("02/02/2005", "02/02/2003", -731);
+        assert_calculate_calendar_day_difference_result("01/05/2005", "02/02/2004", -454);
+        assert_calculate_calendar_day_difference_result("05/01/2004", "02/04/2003", -278);
+        assert_calculate_calendar_day_difference_result("01/10/2000", "02/02/2000", -242);
+        assert_calculate_calendar_day_difference_result("01/10/2002", "02/02/2003", 124);
+        assert_calculate_calendar_day_difference_result("02/01/2040", "02/02/2040", 31);
+        assert_calculate_calendar_day_difference_result("20/02/2003", "02/02/2003", -18);
+        assert_calculate_calendar_day_difference_result("02/02/2003", "02/02/2003", 0);
+    }
+
+    #[test]
+    fn lib_local_date_calculate_calendar_day_difference_leap_year_test() {
+        assert_calculate_calendar_day_difference_result("28/02/2024", "01/03/2024", 2);
+        assert_calculate_calendar_day_difference_result("28/02/2023", "01/03/2023", 1);
+        assert_calculate_calendar_day_difference_result("28/02/1900", "01/03/1900", 1);
+        assert_calculate_calendar_day_difference_result("01/01/2000", "01/01/2001", 366);
+        assert_calculate_calendar_day_difference_result("01/01/1900", "01/01/2100", 73_049);
+    }
+
+    #[test]
+    fn lib_local_date_try_parse_date_formats_test() {
+        let date = LocalDate::try_parse_date("1985-12-12", date_format::YYYYMMDD);
+        assert!(date.is_ok_and(|v| v == LocalDate { day: 12, month: 12, year: 1985 }));
+
+        let date = LocalDate::try_parse_date("01-01-1980", date_format::DDMMYYYY);
+        assert!(date.is_ok_and(|v| v == LocalDate { day: 1, month: 1, year: 1980 }));
+
+        let date = LocalDate::try_parse_date("2024-02-29T23:30:00-05:00", date_format::RFC3339);
+        assert!(date.is_ok_and(|v| v == LocalDate { day: 29, month: 2, year: 2024 }));
+
+        let date = LocalDate::try_parse_date("2024-02-29T23:30:00", date_format::YYYYMMDDHHMMSS);
+        assert!(date.is_ok_and(|v| v == LocalDate { day: 29, month: 2, year: 2024 }));
+    }
+
+    #[test]
+    fn lib_local_date_try_parse_date_validation_test() {
+        let cases = [
+            ("13/13/2003", date_format::DDMMYYYY, LocalDateError::OutOfRangeError),
+            ("00/12/2003", date_format::DDMMYYYY, LocalDateError::OutOfRangeError),
+            ("29/02/2023", date_format::DDMMYYYY, LocalDateError::OutOfRangeError),
+            ("31/04/2023", date_format::DDMMYYYY, LocalDateError::OutOfRangeError),
+            ("2023-02-29", date_format::YYYYMMDD, LocalDateError::OutOfRangeError),
+            ("0000-01-01", date_format::YYYYMMDD, LocalDateError::OutOfRangeError),
+            ("+1/02/2003", date_format::DDMMYYYY, LocalDateError::MalformedDateError),
+            ("01/02/2003/04", date_format::DDMMYYYY, LocalDateError::MalformedDateError),
+            ("01/02/20030", date_format::DDMMYYYY, LocalDateError::MalformedDateError),
+            ("2024-02-30T00:00:00Z", date_format::RFC3339, LocalDateError::MalformedDateError),
+            ("2024-02-29T24:00:00", date_format::YYYYMMDDHHMMSS, LocalDateError::OutOfRangeError),
+            ("2024-02-29 10:00:00", date_format::YYYYMMDDHHMMSS, LocalDateError::MalformedDateError),
+            ("01/02/2003", "DD.MM.YY", LocalDateError::UnsupportedFormatError),
+        ];
+
+        for (date, format, expected_error) in cases {
+            let result = LocalDate::try_parse_date(date, format);
+            assert!(result.is_err_and(|e| e == expected_error), "{} {}", date, format);
+        }
+    }
+
+    #[test]
+    fn lib_local_date_time_calculate_difference_test() {
+        let parse = |date_time: &str, format: &str| LocalDateTime::parse_date_time(date_time, format).unwrap();
+
+        // The same instant in two timezones
+        let utc = parse("2024-03-14T20:00:00Z", date_format::RFC3339);
+        let jakarta = parse("2024-03-15T03:00:00+07:00", date_format::RFC3339);
+        assert!(LocalDateTime::calculate_second_difference(&utc, &jakarta) == 0);
+
+        // A date time without a timezone is taken in the timezone of the other one
+        let naive = parse("2024-03-15T01:30:00", date_format::YYYYMMDDHHMMSS);
+        assert!(naive.utc_offset.is_none());
+        assert!(LocalDateTime::calculate_hour_difference(&naive, &jakarta) == 1);
+        assert!(LocalDateTime::calculate_second_difference(&jakarta, &naive) == -5400);
+
+        // A date is taken at midnight, across the leap day
+        let midnight = parse("28/02/2024", date_format::DDMMYYYY);
+        let later = parse("2024-03-01T06:00:00", date_format::YYYYMMDDHHMMSS);
+        assert!(LocalDateTime::calculate_hour_difference(&midnight, &later) == 54);
+    }
 }
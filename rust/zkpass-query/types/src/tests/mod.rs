@@ -1 +1,22 @@
+#![allow(clippy::module_inception)]
+
+#[cfg(feature = "preview")]
+mod commitment_test;
+#[cfg(feature = "preview")]
+mod decimal_test;
+mod error_test;
+#[cfg(feature = "preview")]
+mod evaluator_test;
 mod lib_test;
+#[cfg(feature = "preview")]
+mod parser_test;
+#[cfg(feature = "preview")]
+mod pseudonym_test;
+#[cfg(feature = "preview")]
+mod regex_test;
+#[cfg(feature = "preview")]
+mod similarity_test;
+#[cfg(feature = "preview")]
+mod text_test;
+#[cfg(feature = "preview")]
+mod typed_test;
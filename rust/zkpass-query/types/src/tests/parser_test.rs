@@ -0,0 +1,616 @@
+#[cfg(test)]
+mod parser_test {
+    use serde_json::json;
+
+    use crate::{
+        ast::{ Case, Definition, Expression, Literal, LiteralSet, Operator, Quantifier, Query, Statement },
+        parse_query,
+        parse_query_for_engine,
+        Decimal,
+        ZkPassQueryEngineError,
+    };
+
+    const DVR_FILES: [&str; 10] = [
+        "array-dvr.json",
+        "basic-dvr.json",
+        "bca-finance-conditional.json",
+        "bca-finance-ramana-dvr.json",
+        "bca-finance-ramana-dvr2.json",
+        "bca-insurance-dewi-dvr.json",
+        "bca-insurance-dewi-dvr-array.json",
+        "employee-onboarding-dvr.json",
+        "health-dvr.json",
+        "multiple/insurance-dvr.json",
+    ];
+
+    #[test]
+    fn parser_parse_dvr_files_test() {
+        for file in DVR_FILES {
+            let query = std::fs::read_to_string(format!("./../../test/data/{}", file)).unwrap();
+            assert!(parse_query(&query).is_ok(), "failed to parse {}", file);
+        }
+    }
+
+    #[test]
+    fn parser_parse_query_ast_test() {
+        let query =
+            json!([
+                { "assign": { "adult": { ">=": [{ "dvar": "items[].age" }, 18, "or"] } } },
+                {
+                    "if": {
+                        "condition": { "lvar": "adult" },
+                        "then": [{ "output": { "name": { "dvar": "name" } } }]
+                    }
+                }
+            ]);
+
+        let parsed = parse_query(&query.to_string()).unwrap();
+        let expected = Query {
+            statements: vec![
+                Statement::Assign {
+                    name: String::from("adult"),
+                    expression: Expression::Comparison {
+                        operator: Operator::Ge,
+                        left: Box::new(Expression::DataVar(String::from("items[].age"))),
+                        right: Box::new(Expression::Int(18)),
+                        quantifier: Quantifier::Any,
+                    },
+                },
+                Statement::If {
+                    condition: Expression::LocalVar(String::from("adult")),
+                    then_block: vec![Statement::Output {
+                        name: String::from("name"),
+                        expression: Expression::DataVar(String::from("name")),
+                    }],
+                    else_block: vec![],
+                }
+            ],
+        };
+        assert!(parsed == expected);
+
+        // The AST round-trips through serde
+        let serialized = serde_json::to_string(&parsed).unwrap();
+        let deserialized: Query = serde_json::from_str(&serialized).unwrap();
+        assert!(deserialized == expected);
+    }
+
+    #[test]
+    fn parser_parse_query_errors_test() {
+        let query =
+            json!([
+                { "assign": { "a": { "~==": [{ "dvar": "name" }] } } },
+                { "print": {} },
+                {
+                    "if": {
+                        "condition": { "lvar": "undefined" },
+                        "then": [{ "output": { "b": { "dvar": "_secret" } } }],
+                        "otherwise": []
+                    }
+                },
+                { "output": { "c": { "a/b": [1, 2] } } }
+            ]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        let found: Vec<(ZkPassQueryEngineError, &str)> = errors
+            .iter()
+            .map(|err| (err.kind.clone(), err.location.as_deref().unwrap()))
+            .collect();
+        assert!(
+            found ==
+                vec![
+                    (ZkPassQueryEngineError::ExpectingSecondOperandParsingError, "/0/assign/a/~0=="),
+                    (ZkPassQueryEngineError::UnknownStmtKeywordParsingError, "/1"),
+                    (ZkPassQueryEngineError::IfStatementUnknownKeywordParsingError, "/2/if/otherwise"),
+                    (ZkPassQueryEngineError::LocalVarParsingError, "/2/if/condition/lvar"),
+                    (ZkPassQueryEngineError::DataVarNameNotStartingWithAlphabetError, "/2/if/then/0/output/b/dvar"),
+                    (ZkPassQueryEngineError::UnexpectedOperatorParsingError, "/3/output/c/a~1b")
+                ]
+        );
+        assert!(errors.iter().all(|err| !err.message.is_empty()));
+    }
+
+    #[test]
+    fn parser_parse_dvr_files_for_engine_test() {
+        for file in DVR_FILES {
+            let query = std::fs::read_to_string(format!("./../../test/data/{}", file)).unwrap();
+            assert!(parse_query_for_engine(&query).is_ok(), "failed to parse {} for the engine", file);
+        }
+    }
+
+    #[test]
+    fn parser_parse_query_for_engine_errors_test() {
+        let query =
+            json!([
+                { "const": { "cities": ["Jakarta", "Bandung"] } },
+                { "assign": { "a": { "get_day_diff": [{ "dvar": "date" }, "YYYY-MM-DD"] } } },
+                { "assign": { "b": { "+": [{ "dvar": "income" }, 1] } } },
+                {
+                    "if": {
+                        "condition": { "in": [{ "dvar": "city" }, { "const": "cities" }] },
+                        "then": [{ "output": { "c": { ">": [{ "dvar": "score", "default": 0 }, 0.5] } } }]
+                    }
+                }
+            ]);
+
+        // The query is valid, but the engine runs none of its statements
+        assert!(parse_query(&query.to_string()).is_ok());
+        let errors = parse_query_for_engine(&query.to_string()).unwrap_err();
+        let found: Vec<(ZkPassQueryEngineError, &str, &str)> = errors
+            .iter()
+            .map(|err| (err.kind.clone(), err.location.as_deref().unwrap(), err.token.as_deref().unwrap()))
+            .collect();
+        assert!(
+            found ==
+                vec![
+                    (ZkPassQueryEngineError::UnknownStmtKeywordParsingError, "/0", "const"),
+                    (ZkPassQueryEngineError::UnexpectedValueError, "/1/assign/a/get_day_diff/1", "YYYY-MM-DD"),
+                    (ZkPassQueryEngineError::UnexpectedOperatorParsingError, "/2/assign/b/+", "+"),
+                    (ZkPassQueryEngineError::UnexpectedOperatorParsingError, "/3/if/condition/in", "in"),
+                    (ZkPassQueryEngineError::QueryParsingError, "/3/if/then/0/output/c/>/0", "default"),
+                    (ZkPassQueryEngineError::UnexpectedValueError, "/3/if/then/0/output/c/>/1", "0.5")
+                ]
+        );
+        assert!(errors.iter().all(|err| err.message.starts_with("the proving engine does not support")));
+    }
+
+    #[test]
+    fn parser_parse_query_not_an_array_test() {
+        let errors = parse_query("{}").unwrap_err();
+        assert!(errors.len() == 1);
+        assert!(errors[0].kind == ZkPassQueryEngineError::DvrNotAnArrayError);
+
+        let errors = parse_query("[").unwrap_err();
+        assert!(errors[0].kind == ZkPassQueryEngineError::QueryParsingError);
+    }
+
+    #[test]
+    fn parser_parse_query_invalid_dvar_path_test() {
+        let query = json!([{ "output": { "a": { "dvar": "items[?" } } }]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        assert!(errors[0].kind == ZkPassQueryEngineError::DataVarParsingError);
+        assert!(errors[0].location.as_deref() == Some("/0/output/a/dvar"));
+        assert!(errors[0].token.as_deref() == Some("items[?"));
+    }
+
+    #[test]
+    fn parser_parse_query_dvar_functions_test() {
+        let query =
+            json!([
+                { "output": { "a": { "dvar": "length(items[?age > `40`])" } } },
+                { "output": { "b": { "dvar": "sum(items[].age)" } } },
+                { "output": { "c": { "dvar": "items[?contains(tags, nofn(@))]" } } },
+                { "output": { "d": { "dvar": "floor(items[0].price)" } } }
+            ]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        assert!(errors.len() == 3);
+        assert!(errors.iter().all(|err| err.kind == ZkPassQueryEngineError::DataVarParsingError));
+        assert!(errors[0].location.as_deref() == Some("/1/output/b/dvar"));
+        assert!(errors[0].message.contains("the function 'sum' is not supported"));
+        assert!(errors[1].location.as_deref() == Some("/2/output/c/dvar"));
+        assert!(errors[1].message.contains("the function 'nofn' is not supported"));
+        assert!(errors[2].location.as_deref() == Some("/3/output/d/dvar"));
+        assert!(errors[2].message.contains("the function 'floor' is not supported"));
+    }
+
+    #[test]
+    fn parser_parse_query_array_operators_test() {
+        let query =
+            json!([
+                { "output": { "a": { "count": [{ "dvar": "items" }, { ">": [{ "ivar": "age" }, 40] }] } } },
+                { "output": { "b": { "sum": [{ "dvar": "items" }] } } },
+                { "output": { "c": { "==": [{ "ivar": "age" }, 40] } } },
+                { "output": { "d": { "filter": [{ "ivar": "items" }, true] } } },
+                { "output": { "e": { "filter": [{ "dvar": "items" }] } } },
+                { "output": { "f": { "map": [{ "dvar": "items" }, { "ivar": "" }] } } }
+            ]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        let found: Vec<(ZkPassQueryEngineError, &str)> = errors
+            .iter()
+            .map(|err| (err.kind.clone(), err.location.as_deref().unwrap()))
+            .collect();
+        assert!(
+            found ==
+                vec![
+                    (ZkPassQueryEngineError::ItemVarParsingError, "/2/output/c/==/0/ivar"),
+                    (ZkPassQueryEngineError::ItemVarParsingError, "/3/output/d/filter/0/ivar"),
+                    (ZkPassQueryEngineError::ExpectingSecondOperandParsingError, "/4/output/e/filter"),
+                    (ZkPassQueryEngineError::ItemVarParsingError, "/5/output/f/map/1/ivar")
+                ]
+        );
+    }
+
+    #[test]
+    fn parser_parse_query_const_test() {
+        let query =
+            json!([
+                { "const": { "countries": ["SG", "ID", 1.0, true, "ID", 1] } },
+                { "output": { "a": { "in": [{ "dvar": "country" }, ["b", "a"]] } } }
+            ]);
+
+        let parsed = parse_query(&query.to_string()).unwrap();
+        let expected = vec![
+            Statement::Const {
+                name: String::from("countries"),
+                values: LiteralSet::new(
+                    vec![
+                        Literal::Bool(true),
+                        Literal::Number(Decimal::from_i64(1)),
+                        Literal::Str(String::from("ID")),
+                        Literal::Str(String::from("SG"))
+                    ]
+                ),
+            },
+            Statement::Output {
+                name: String::from("a"),
+                expression: Expression::Operation {
+                    operator: Operator::In,
+                    operands: vec![
+                        Expression::DataVar(String::from("country")),
+                        Expression::Set(LiteralSet::new(vec![Literal::Str(String::from("a")), Literal::Str(String::from("b"))]))
+                    ],
+                },
+            }
+        ];
+        assert!(parsed.statements == expected);
+    }
+
+    #[test]
+    fn parser_literal_set_deserialize_test() {
+        // A deserialized set is sorted and deduplicated, so its membership is still a binary search
+        let set: LiteralSet = serde_json::from_str(
+            r#"{"literals":[{"Str":"SG"},{"Str":"ID"},{"Bool":true},{"Str":"ID"},{"Number":{"units":5,"scale":1}}]}"#
+        ).unwrap();
+        let expected = LiteralSet::new(
+            vec![
+                Literal::Bool(true),
+                Literal::Number(Decimal::new(5, 1).unwrap()),
+                Literal::Str(String::from("ID")),
+                Literal::Str(String::from("SG"))
+            ]
+        );
+        assert!(set == expected);
+        assert!(set.contains(&Literal::Str(String::from("ID"))));
+        assert!(set.contains(&Literal::Str(String::from("SG"))));
+        assert!(!set.contains(&Literal::Str(String::from("MY"))));
+
+        let round_trip: LiteralSet = serde_json::from_str(&serde_json::to_string(&set).unwrap()).unwrap();
+        assert!(round_trip == set);
+    }
+
+    #[test]
+    fn parser_parse_query_const_errors_test() {
+        let query =
+            json!([
+                { "output": { "a": { "in": [{ "dvar": "country" }, { "const": "countries" }] } } },
+                { "const": { "countries": ["ID"] } },
+                { "const": { "countries": ["SG"] } },
+                { "const": { "mixed": ["ID", { "dvar": "country" }, null] } },
+                { "const": { "a": [], "b": [] } },
+                { "if": { "condition": true, "then": [{ "const": { "nested": ["ID"] } }] } }
+            ]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        assert!(errors.iter().all(|err| err.kind == ZkPassQueryEngineError::ConstStatementParsingError));
+        let locations: Vec<&str> = errors
+            .iter()
+            .map(|err| err.location.as_deref().unwrap())
+            .collect();
+        assert!(
+            locations ==
+                vec![
+                    "/0/output/a/in/1/const",
+                    "/2/const/countries",
+                    "/3/const/mixed/1",
+                    "/3/const/mixed/2",
+                    "/4/const",
+                    "/5/if/then/0/const"
+                ]
+        );
+    }
+
+    #[test]
+    fn parser_parse_query_dvar_default_test() {
+        let query =
+            json!([
+                { "output": { "a": { "dvar": "rating", "default": 0 } } },
+                { "output": { "b": { "exists": [{ "dvar": "rating" }] } } }
+            ]);
+
+        let parsed = parse_query(&query.to_string()).unwrap();
+        let expected = vec![
+            Statement::Output {
+                name: String::from("a"),
+                expression: Expression::DataVarOrDefault {
+                    path: String::from("rating"),
+                    default: Box::new(Expression::Int(0)),
+                },
+            },
+            Statement::Output {
+                name: String::from("b"),
+                expression: Expression::Operation {
+                    operator: Operator::Exists,
+                    operands: vec![Expression::DataVar(String::from("rating"))],
+                },
+            }
+        ];
+        assert!(parsed.statements == expected);
+
+        let query =
+            json!([
+                { "output": { "a": { "dvar": "1rating", "default": null } } },
+                { "output": { "b": { "dvar": "rating", "fallback": 0 } } },
+                { "output": { "c": { "is_null": [{ "dvar": "rating", "default": 0 }] } } },
+                { "output": { "d": { "exists": [] } } }
+            ]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        let errors: Vec<(ZkPassQueryEngineError, &str)> = errors
+            .iter()
+            .map(|err| (err.kind.clone(), err.location.as_deref().unwrap()))
+            .collect();
+        assert!(
+            errors ==
+                vec![
+                    (ZkPassQueryEngineError::DataVarNameNotStartingWithAlphabetError, "/0/output/a/dvar"),
+                    (ZkPassQueryEngineError::UnexpectedValueError, "/0/output/a/default"),
+                    (ZkPassQueryEngineError::QueryParsingError, "/1/output/b"),
+                    (ZkPassQueryEngineError::UnexpectedValueError, "/2/output/c/is_null/0"),
+                    (ZkPassQueryEngineError::ExpectingFirstOperandParsingError, "/3/output/d/exists")
+                ]
+        );
+    }
+
+    #[test]
+    fn parser_parse_query_switch_test() {
+        let query =
+            json!([
+                {
+                    "switch": {
+                        "cases": [{ "condition": { ">=": [{ "dvar": "score" }, 700] }, "then": [{ "output": { "tier": "A" } }] }],
+                        "default": [{ "output": { "tier": "B" } }]
+                    }
+                }
+            ]);
+
+        let parsed = parse_query(&query.to_string()).unwrap();
+        let expected = vec![Statement::Switch {
+            cases: vec![Case {
+                condition: Expression::Comparison {
+                    operator: Operator::Ge,
+                    left: Box::new(Expression::DataVar(String::from("score"))),
+                    right: Box::new(Expression::Int(700)),
+                    quantifier: Quantifier::All,
+                },
+                block: vec![Statement::Output { name: String::from("tier"), expression: Expression::Str(String::from("A")) }],
+            }],
+            default_block: vec![Statement::Output { name: String::from("tier"), expression: Expression::Str(String::from("B")) }],
+        }];
+        assert!(parsed.statements == expected);
+    }
+
+    #[test]
+    fn parser_parse_query_switch_errors_test() {
+        let query =
+            json!([
+                { "switch": [] },
+                { "switch": { "cases": [] } },
+                { "switch": { "cases": [{ "condition": true }, { "then": [] }, 1], "otherwise": [] } },
+                {
+                    "switch": {
+                        "cases": [
+                            { "condition": true, "then": [], "else": [] },
+                            { "condition": false, "then": [{ "const": { "a": [] } }] }
+                        ],
+                        "default": {}
+                    }
+                },
+                {
+                    "switch": {
+                        "cases": [
+                            { "condition": { "==": [1, 1] }, "then": [] },
+                            { "condition": true, "then": [] },
+                            { "condition": false, "then": [] }
+                        ]
+                    }
+                }
+            ]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        let errors: Vec<(ZkPassQueryEngineError, &str)> = errors
+            .iter()
+            .map(|err| (err.kind.clone(), err.location.as_deref().unwrap()))
+            .collect();
+        assert!(
+            errors ==
+                vec![
+                    (ZkPassQueryEngineError::SwitchStatementParsingError, "/0/switch"),
+                    (ZkPassQueryEngineError::SwitchStatementParsingError, "/1/switch"),
+                    (ZkPassQueryEngineError::SwitchStatementParsingError, "/2/switch/otherwise"),
+                    (ZkPassQueryEngineError::SwitchStatementParsingError, "/2/switch/cases/0"),
+                    (ZkPassQueryEngineError::SwitchStatementParsingError, "/2/switch/cases/1"),
+                    (ZkPassQueryEngineError::SwitchStatementParsingError, "/2/switch/cases/2"),
+                    (ZkPassQueryEngineError::SwitchStatementParsingError, "/3/switch/cases/0/else"),
+                    (ZkPassQueryEngineError::ConstStatementParsingError, "/3/switch/cases/1/then/0/const"),
+                    (ZkPassQueryEngineError::UnexpectedStmtError, "/3/switch/default"),
+                    (ZkPassQueryEngineError::SwitchStatementParsingError, "/4/switch/cases/2")
+                ]
+        );
+    }
+
+    #[test]
+    fn parser_parse_query_define_test() {
+        let query =
+            json!([
+                {
+                    "define": {
+                        "adult": { ">=": [{ "dvar": "age" }, { "ref": "min_age" }] },
+                        "min_age": 18,
+                        "older": { "params": ["age"], "body": { ">": [{ "dvar": "age" }, { "param": "age" }] } }
+                    }
+                },
+                { "output": { "a": { "ref": ["older", { "ref": "min_age" }] } } }
+            ]);
+
+        let parsed = parse_query(&query.to_string()).unwrap();
+        let min_age = Expression::Reference { name: String::from("min_age"), args: vec![] };
+        let expected = vec![
+            Statement::Define {
+                definitions: vec![
+                    Definition {
+                        name: String::from("adult"),
+                        params: vec![],
+                        body: Expression::Comparison {
+                            operator: Operator::Ge,
+                            left: Box::new(Expression::DataVar(String::from("age"))),
+                            right: Box::new(min_age.clone()),
+                            quantifier: Quantifier::All,
+                        },
+                    },
+                    Definition { name: String::from("min_age"), params: vec![], body: Expression::Int(18) },
+                    Definition {
+                        name: String::from("older"),
+                        params: vec![String::from("age")],
+                        body: Expression::Comparison {
+                            operator: Operator::Gt,
+                            left: Box::new(Expression::DataVar(String::from("age"))),
+                            right: Box::new(Expression::Param(String::from("age"))),
+                            quantifier: Quantifier::All,
+                        },
+                    }
+                ],
+            },
+            Statement::Output {
+                name: String::from("a"),
+                expression: Expression::Reference { name: String::from("older"), args: vec![min_age] },
+            }
+        ];
+        assert!(parsed.statements == expected);
+    }
+
+    #[test]
+    fn parser_parse_query_define_errors_test() {
+        let query =
+            json!([
+                { "output": { "a": { "ref": "adult" } } },
+                {
+                    "define": {
+                        "a": { "ref": "b" },
+                        "b": { "and": [true, { "ref": "c" }] },
+                        "c": { "ref": "a" },
+                        "d": { "ref": "d" },
+                        "e": { "params": ["x", "x"], "body": true },
+                        "f": { "params": ["x"], "body": { "param": "y" }, "doc": "" }
+                    }
+                },
+                { "define": { "a": true } },
+                { "output": { "b": { "ref": ["f"] } } },
+                { "output": { "c": { "param": "x" } } },
+                { "if": { "condition": true, "then": [{ "define": { "g": true } }] } }
+            ]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        assert!(errors.iter().all(|err| err.kind == ZkPassQueryEngineError::DefineStatementParsingError));
+        let locations: Vec<&str> = errors
+            .iter()
+            .map(|err| err.location.as_deref().unwrap())
+            .collect();
+        assert!(
+            locations ==
+                vec![
+                    "/0/output/a/ref",
+                    "/1/define/e/params/1",
+                    "/1/define/f/doc",
+                    "/1/define/f/body/param",
+                    "/1/define/a",
+                    "/1/define/b",
+                    "/1/define/c",
+                    "/1/define/d",
+                    "/2/define/a",
+                    "/3/output/b/ref",
+                    "/4/output/c/param",
+                    "/5/if/then/0/define"
+                ]
+        );
+        assert!(errors[4].message == "the definition 'a' refers to itself: a -> b -> c -> a");
+        assert!(errors[7].message == "the definition 'd' refers to itself: d -> d");
+    }
+
+    #[test]
+    fn parser_parse_query_text_errors_test() {
+        let query =
+            json!([
+                { "text": { "normalization": "nfd", "case_folding": "turkic", "locale": "tr" } },
+                { "text": { "normalization": "nfc" } },
+                { "if": { "condition": true, "then": [{ "text": { "case_folding": "full" } }] } }
+            ]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        assert!(errors.iter().all(|err| err.kind == ZkPassQueryEngineError::TextStatementParsingError));
+        let mut locations: Vec<&str> = errors
+            .iter()
+            .map(|err| err.location.as_deref().unwrap())
+            .collect();
+        locations.sort();
+        assert!(
+            locations == vec!["/0/text/locale", "/0/text/normalization", "/1/text", "/2/if/then/0/text"]
+        );
+        assert!(errors.iter().any(|err| err.message == "unknown normalization \"nfd\""));
+    }
+
+    #[test]
+    fn parser_parse_query_inexact_number_test() {
+        let query =
+            r#"[
+                { "output": { "a": { "==": [{ "dvar": "balance" }, 170141183460469231731687303715884105727e-18] } } },
+                { "output": { "b": { "==": [{ "dvar": "balance" }, 12.5] } } },
+                { "output": { "c": { "in": [{ "dvar": "balance" }, [1, 12345678901234567.89]] } } }
+            ]"#;
+
+        let errors = parse_query(query).unwrap_err();
+        assert!(errors.iter().all(|err| err.kind == ZkPassQueryEngineError::InexactNumberError));
+        let mut located: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|err| (err.location.as_deref().unwrap(), err.token.as_deref().unwrap()))
+            .collect();
+        located.sort();
+        assert!(
+            located ==
+                vec![
+                    ("/0/output/a/==/1", "170141183460469231731687303715884105727e-18"),
+                    ("/2/output/c/in/1/1", "12345678901234567.89")
+                ]
+        );
+    }
+
+    #[test]
+    fn parser_parse_query_blinded_in_errors_test() {
+        let hash = "f96f100068fb396cb122d4fab1cec1efa3509beb2db8339693ccadaecc074efe";
+        let query =
+            json!([
+                { "output": { "a": { "blinded_in": [{ "dvar": "docId" }, "0001", [hash]] } } },
+                { "output": { "b": { "blinded_in": [{ "dvar": "docId" }, { "dvar": "salt" }, hash] } } },
+                { "output": { "c": { "blinded_in": [{ "dvar": "docId" }, "000102030405060708090a0b0c0d0e0f", []] } } },
+                { "output": { "d": { "blinded_in": [{ "dvar": "docId" }, "000102030405060708090a0b0c0d0e0f", [hash.to_uppercase()]] } } },
+                { "output": { "e": { "blinded_in": [{ "dvar": "docId" }, "000102030405060708090a0b0c0d0e0f", [{ "dvar": "hash" }]] } } }
+            ]);
+
+        let errors = parse_query(&query.to_string()).unwrap_err();
+        let errors: Vec<(ZkPassQueryEngineError, &str)> = errors
+            .iter()
+            .map(|err| (err.kind.clone(), err.location.as_deref().unwrap()))
+            .collect();
+        assert!(
+            errors ==
+                vec![
+                    (ZkPassQueryEngineError::SaltError, "/0/output/a/blinded_in/1"),
+                    (ZkPassQueryEngineError::SaltError, "/1/output/b/blinded_in/1"),
+                    (ZkPassQueryEngineError::UnexpectedValueError, "/2/output/c/blinded_in/2"),
+                    (ZkPassQueryEngineError::UnexpectedValueError, "/3/output/d/blinded_in/2"),
+                    (ZkPassQueryEngineError::UnexpectedValueError, "/4/output/e/blinded_in/2")
+                ]
+        );
+    }
+}
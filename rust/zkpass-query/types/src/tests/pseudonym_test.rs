@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod pseudonym_test {
+    use crate::{ pseudonym::{ pseudonym, verifier_id }, DvrVerifyingKey, Val };
+
+    #[test]
+    fn pseudonym_derivation_test() {
+        let customer_id = Val::Str(String::from("BCA123756108"));
+        let bca = pseudonym("did:example:bca", "", &customer_id);
+        assert!(bca == "e36dd2ee95ad2016d8103032d5fd534a420914a84933fc8376620ce096d8956e");
+        assert!(
+            pseudonym("did:example:bca", "promo-2024", &customer_id) ==
+                "a30400ddfd8868556a8132c12b7db23a29cfa1b2f75bd4951a98b3dbfc7db54b"
+        );
+        assert!(pseudonym("did:example:bca", "", &customer_id) == bca);
+        assert!(pseudonym("did:example:bank", "", &customer_id) != bca);
+
+        // The lengths keep the verifier identity and the context apart
+        assert!(pseudonym("ab", "c", &customer_id) != pseudonym("a", "bc", &customer_id));
+    }
+
+    #[test]
+    fn pseudonym_verifier_id_test() {
+        let key = DvrVerifyingKey { x: String::from("bca-x"), y: String::from("bca-y") };
+        assert!(verifier_id(&key) == "24cbc03932b5d8913a78523fe828473506b647509b4d442e1f27134085fc014b");
+
+        // The lengths keep the parameters apart
+        let shifted = DvrVerifyingKey { x: String::from("bca-"), y: String::from("xbca-y") };
+        assert!(verifier_id(&shifted) != verifier_id(&key));
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod regex_test {
+    use crate::regex::Regex;
+
+    fn is_match(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern, false).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn regex_match_test() {
+        assert!(is_match(r"[A-Z]{2}\d{8}", "DL12345678"));
+        assert!(!is_match(r"[A-Z]{2}\d{8}", "DL1234567"));
+        assert!(!is_match(r"[A-Z]{2}\d{8}", "DL123456789"));
+        assert!(is_match(r"\+62-\d{3}-\d{3}-\d{4}", "+62-855-123-4567"));
+        assert!(is_match(r"[\w.]+@(company|corp)\.com", "jane.doe@corp.com"));
+        assert!(!is_match(r"[\w.]+@(company|corp)\.com", "jane.doe@corp.co"));
+        assert!(is_match("a.c", "abc"));
+        assert!(is_match("ab*c?", "abbb"));
+        assert!(is_match("(ab)+", "ababab"));
+        assert!(!is_match("(ab)+", ""));
+        assert!(is_match("x{2,}", "xxxxx"));
+        assert!(is_match("x{0,2}", ""));
+        assert!(!is_match("x{1,2}", "xxx"));
+        assert!(is_match("[^ ]+", "no-spaces"));
+        assert!(!is_match("[^ ]+", "with space"));
+        assert!(is_match(r"[a\-z]*", "a-z"));
+        assert!(is_match("", ""));
+        assert!(is_match("é+", "ééé"));
+
+        // A pattern matches the whole string
+        assert!(!is_match("abc", "xabcx"));
+        assert!(is_match(".*abc.*", "xabcx"));
+    }
+
+    #[test]
+    fn regex_ignore_case_test() {
+        let regex = Regex::new(r"[A-Z]{2}\d+", true).unwrap();
+        assert!(regex.is_match("dl123"));
+        assert!(regex.is_match("DL123"));
+
+        let regex = Regex::new("straße", true).unwrap();
+        assert!(regex.is_match("STRASSE"));
+        assert!(regex.is_match("Straße"));
+        assert!(!Regex::new("straße", false).unwrap().is_match("STRASSE"));
+    }
+
+    #[test]
+    fn regex_linear_time_test() {
+        // A backtracking engine takes exponential time on this pattern
+        let regex = Regex::new("(a*)*(a|b)*c", false).unwrap();
+        assert!(!regex.is_match(&"a".repeat(10_000)));
+        assert!(regex.is_match(&format!("{}c", "ab".repeat(10_000))));
+    }
+
+    #[test]
+    fn regex_error_test() {
+        for (pattern, position) in [
+            ("(ab", 3),
+            ("ab)", 2),
+            ("[a-", 3),
+            ("[z-a]", 4),
+            ("*a", 1),
+            ("a**", 2),
+            ("^abc", 1),
+            ("abc$", 4),
+            (r"\q", 2),
+            ("a{3,1}", 5),
+            ("a{101}", 5),
+            ("a{", 2),
+        ] {
+            let err = Regex::new(pattern, false).unwrap_err();
+            assert!(err.position == position, "{}: {}", pattern, err);
+            assert!(!err.message.is_empty());
+        }
+
+        let err = Regex::new("((a{100}){100}){100}", false).unwrap_err();
+        assert!(err.message == "the pattern is too large");
+    }
+}
@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod similarity_test {
+    use std::cmp::Ordering;
+
+    use crate::{ similarity::jaro_winkler, Decimal };
+
+    fn decimal(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn similarity_jaro_winkler_test() {
+        assert!(jaro_winkler("MARTHA", "MARHTA").to_decimal() == decimal("0.9611"));
+        assert!(jaro_winkler("DWAYNE", "DUANE").to_decimal() == decimal("0.84"));
+        assert!(jaro_winkler("DIXON", "DICKSONX").to_decimal() == decimal("0.8133"));
+        assert!(jaro_winkler("MARTHA", "MARTHA").to_decimal() == decimal("1"));
+        assert!(jaro_winkler("", "").to_decimal() == decimal("1"));
+        assert!(jaro_winkler("ABC", "").to_decimal() == decimal("0"));
+        assert!(jaro_winkler("ABC", "XYZ").to_decimal() == decimal("0"));
+
+        // Below 0.7, the common prefix is not boosted
+        assert!(jaro_winkler("ABCD", "ABXY").to_decimal() == jaro_winkler("DCBA", "YXBA").to_decimal());
+    }
+
+    #[test]
+    fn similarity_threshold_test() {
+        // The exact score is 0.961111..., so it is above its truncated decimal
+        let score = jaro_winkler("MARTHA", "MARHTA");
+        assert!(score.cmp_decimal(&decimal("0.9611")) == Ordering::Greater);
+        assert!(score.cmp_decimal(&decimal("0.9612")) == Ordering::Less);
+        assert!(jaro_winkler("DWAYNE", "DUANE").cmp_decimal(&decimal("0.84")) == Ordering::Equal);
+        assert!(jaro_winkler("ABC", "XYZ").cmp_decimal(&Decimal::from_i64(0)) == Ordering::Equal);
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod text_test {
+    use crate::text::{ CaseFolding, Normalization, TextOptions };
+
+    #[test]
+    fn text_options_test() {
+        let nfd = "Jose\u{301}";
+        let nfc = "Jos\u{e9}";
+
+        let text = TextOptions::default();
+        assert!(!text.equal(nfd, nfc));
+        assert!(text.fold_case("Straße") == "strasse");
+        assert!(text.fold_case("I") == "i");
+        assert!(text.lowercase("STRAßE") == "straße");
+        assert!(text.lowercase("ΟΔΟΣ") == "οδοσ");
+
+        let text = TextOptions { normalization: Normalization::Nfc, ..TextOptions::default() };
+        assert!(text.equal(nfd, nfc));
+        assert!(text.normalize(nfd) == nfc);
+        assert!(!text.equal("\u{fb01}le", "file"));
+        assert!(text.fold_case("JOSE\u{301}") == nfc.to_lowercase());
+
+        let text = TextOptions { normalization: Normalization::Nfkc, ..TextOptions::default() };
+        assert!(text.equal("\u{fb01}le", "file"));
+        assert!(text.equal("\u{ff2a}\u{ff4f}\u{ff53}\u{e9}", nfd));
+
+        let text = TextOptions { case_folding: CaseFolding::Turkic, ..TextOptions::default() };
+        assert!(text.fold_case("I") == "\u{131}");
+        assert!(text.fold_case("D\u{130}YARBAKIR") == "diyarbak\u{131}r");
+        assert!(text.fold_case("Straße") == "strasse");
+        assert!(text.lowercase("D\u{130}YARBAKIR") == "diyarbak\u{131}r");
+
+        let text = TextOptions { case_folding: CaseFolding::Full, ..TextOptions::default() };
+        assert!(text.lowercase("Straße") == "strasse");
+        assert!(text.lowercase("ΟΔΟΣ") == "οδοσ");
+        assert!(text.lowercase("ς") == "σ");
+    }
+}
@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod typed_test {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use crate::{ Decimal, OutputReader, OutputTypeError };
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Tier {
+        Gold,
+        Silver,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        name: String,
+        price: Decimal,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Address {
+        city: String,
+        zip: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct QueryResult {
+        result: bool,
+        age: u8,
+        score: f64,
+        tier: Tier,
+        address: Address,
+        items: Vec<Item>,
+        nickname: Option<String>,
+    }
+
+    fn typed<T: for<'de> Deserialize<'de>>(data: serde_json::Value) -> Result<T, OutputTypeError> {
+        OutputReader::from_json(&data.to_string()).unwrap().into_typed::<T>()
+    }
+
+    #[test]
+    fn typed_into_typed_test() {
+        let data =
+            json!({
+                "result": true,
+                "age": 30,
+                "score": 7.25,
+                "tier": "Gold",
+                "address": { "city": "Jakarta", "zip": null },
+                "items": [{ "name": "a", "price": 12.345 }, { "name": "b", "price": 7 }],
+                "unused": [1, 2]
+            });
+
+        let result: QueryResult = typed(data).unwrap();
+        let expected = QueryResult {
+            result: true,
+            age: 30,
+            score: 7.25,
+            tier: Tier::Gold,
+            address: Address { city: String::from("Jakarta"), zip: None },
+            items: vec![
+                Item { name: String::from("a"), price: Decimal::new(12345, 3).unwrap() },
+                Item { name: String::from("b"), price: Decimal::from_i64(7) }
+            ],
+            nickname: None,
+        };
+        assert!(result == expected);
+    }
+
+    #[test]
+    fn typed_into_typed_errors_test() {
+        #[derive(Debug, Deserialize)]
+        struct Outcome {
+            #[allow(dead_code)]
+            result: bool,
+        }
+        let err = typed::<Outcome>(json!({ "outcome": true })).unwrap_err();
+        assert!(err.path.is_empty());
+        assert!(err.message == "missing field `result`");
+
+        let err = typed::<Outcome>(json!({ "result": "yes" })).unwrap_err();
+        assert!(err.path == "result");
+        assert!(err.to_string() == "output 'result': invalid type: string \"yes\", expected a boolean");
+
+        let data =
+            json!({
+                "result": true,
+                "age": 300,
+                "score": 1,
+                "tier": "Gold",
+                "address": { "city": "Jakarta" },
+                "items": []
+            });
+        let err = typed::<QueryResult>(data).unwrap_err();
+        assert!(err.path == "age");
+
+        let data =
+            json!({
+                "result": true,
+                "age": 30,
+                "score": 1,
+                "tier": "Bronze",
+                "address": { "city": "Jakarta" },
+                "items": []
+            });
+        let err = typed::<QueryResult>(data).unwrap_err();
+        assert!(err.path == "tier");
+
+        let data =
+            json!({
+                "result": true,
+                "age": 30,
+                "score": 1,
+                "tier": "Silver",
+                "address": { "city": 1 },
+                "items": [{ "name": "a", "price": 1 }, { "name": "b", "price": "free" }]
+            });
+        let err = typed::<QueryResult>(data).unwrap_err();
+        assert!(err.path == "address.city");
+
+        let data =
+            json!({
+                "result": true,
+                "age": 30,
+                "score": 1,
+                "tier": "Silver",
+                "address": { "city": "Jakarta" },
+                "items": [{ "name": "a", "price": 1 }, { "name": "b", "price": "free" }]
+            });
+        let err = typed::<QueryResult>(data).unwrap_err();
+        assert!(err.path == "items[1].price");
+    }
+
+    #[test]
+    fn typed_reserved_outputs_test() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        struct Outcome {
+            result: bool,
+        }
+        let data =
+            json!({
+                "result": true,
+                "_zkpass_reference_time_": "2024-06-01T00:00:00Z",
+                "_zkpass_verifier_id_": "verifier-a"
+            });
+        let output_reader = OutputReader::from_json(&data.to_string()).unwrap();
+        assert!(output_reader.verifier_id().as_deref() == Some("verifier-a"));
+        assert!(output_reader.into_typed::<Outcome>() == Ok(Outcome { result: true }));
+
+        let err = typed::<Outcome>(json!({ "result": true, "extra": 1 })).unwrap_err();
+        assert!(err.message.contains("unknown field `extra`"));
+    }
+
+    #[test]
+    fn typed_struct_named_decimal_test() {
+        // An application struct is not taken for a decimal, whatever its name and fields
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Decimal {
+            units: i128,
+            scale: u32,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Reading {
+            value: Decimal,
+        }
+        let result: Reading = typed(json!({ "value": { "units": 7, "scale": 2 } })).unwrap();
+        assert!(result == Reading { value: Decimal { units: 7, scale: 2 } });
+
+        let err = typed::<Reading>(json!({ "value": 7 })).unwrap_err();
+        assert!(err.path == "value");
+    }
+
+    #[test]
+    fn typed_nested_array_path_test() {
+        #[derive(Debug, Deserialize)]
+        struct Matrix {
+            #[allow(dead_code)]
+            rows: Vec<Vec<i64>>,
+        }
+        let err = typed::<Matrix>(json!({ "rows": [[1], [2, "x"]] })).unwrap_err();
+        assert!(err.path == "rows[1][1]");
+    }
+}
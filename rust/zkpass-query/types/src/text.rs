@@ -0,0 +1,127 @@
+//
+// Text options of the string comparisons.
+//
+// A query selects them with a `text` statement, e.g. `{ "text": { "normalization": "nfc", "case_folding": "turkic" } }`.
+// Without it, strings are compared as they are, `~==` uses the full Unicode case folding, and
+// `~contains`, `~starts_with` and `~ends_with` only lowercase the strings, so the existing queries
+// keep their results.
+//
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use caseless::default_case_fold_str;
+use unicode_normalization::{is_nfc_quick, is_nfkc_quick, IsNormalized, UnicodeNormalization};
+
+///
+/// The Unicode normalization applied to both strings of a comparison.
+///
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum Normalization {
+    /// "none": the strings are compared as they are
+    #[default]
+    None,
+
+    /// "nfc": canonically equivalent strings are equal, e.g. "José" with a combining accent and with "é"
+    Nfc,
+
+    /// "nfkc": compatibility equivalent strings are equal too, e.g. "ﬁ" and "fi", or "Ｊｏｓé" and "José"
+    Nfkc,
+}
+
+impl Normalization {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Normalization::None),
+            "nfc" => Some(Normalization::Nfc),
+            "nfkc" => Some(Normalization::Nfkc),
+            _ => None,
+        }
+    }
+}
+
+///
+/// The case folding of the case-insensitive operators, e.g. `~==`.
+///
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum CaseFolding {
+    /// no case folding selected: `~==` uses the full Unicode case folding, while `~contains`, `~starts_with`
+    /// and `~ends_with` lowercase each character, e.g. "STRASSE" does not contain "ß"
+    #[default]
+    Lowercase,
+
+    /// "full": the full Unicode case folding, e.g. "Straße" is "strasse"
+    Full,
+
+    /// "turkic": the full Unicode case folding with the Turkish and Azerbaijani dotted and dotless i,
+    /// i.e. "I" is "ı" and "İ" is "i"
+    Turkic,
+}
+
+impl CaseFolding {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "full" => Some(CaseFolding::Full),
+            "turkic" => Some(CaseFolding::Turkic),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TextOptions {
+    pub normalization: Normalization,
+    pub case_folding: CaseFolding,
+}
+
+impl TextOptions {
+    // Normalizes a string, without copying it when it is already normalized.
+    pub fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        match self.normalization {
+            Normalization::None => Cow::Borrowed(s),
+            Normalization::Nfc if is_nfc_quick(s.chars()) == IsNormalized::Yes => Cow::Borrowed(s),
+            Normalization::Nfc => Cow::Owned(s.nfc().collect()),
+            Normalization::Nfkc if is_nfkc_quick(s.chars()) == IsNormalized::Yes => Cow::Borrowed(s),
+            Normalization::Nfkc => Cow::Owned(s.nfkc().collect()),
+        }
+    }
+
+    // Maps the letters that the case folding folds differently, before the full case folding.
+    pub fn prefold<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        match self.case_folding {
+            CaseFolding::Turkic if s.contains(['I', '\u{130}']) => {
+                Cow::Owned(
+                    s
+                        .chars()
+                        .map(|c| match c {
+                            'I' => '\u{131}',
+                            '\u{130}' => 'i',
+                            c => c,
+                        })
+                        .collect()
+                )
+            }
+            _ => Cow::Borrowed(s),
+        }
+    }
+
+    // Folds the case of a normalized string, e.g. "Straße" is "strasse", and normalizes the result again,
+    // because the folding may break the normalization, e.g. of a precomposed letter with no folded form.
+    pub fn fold_case(&self, s: &str) -> String {
+        let folded = default_case_fold_str(&self.prefold(&self.normalize(s)));
+        self.normalize(&folded).into_owned()
+    }
+
+    // Lowers the case of a normalized string for `~contains`, `~starts_with` and `~ends_with`.
+    // Each character is lowercased on its own, e.g. "ΟΔΟΣ" is "οδοσ", unless a case folding is selected.
+    pub fn lowercase(&self, s: &str) -> String {
+        if self.case_folding != CaseFolding::Lowercase {
+            return self.fold_case(s);
+        }
+        let lowercased: String = self.normalize(s).chars().flat_map(char::to_lowercase).collect();
+        self.normalize(&lowercased).into_owned()
+    }
+
+    // Checks whether two strings are equal after the normalization.
+    pub fn equal(&self, a: &str, b: &str) -> bool {
+        a == b || (self.normalization != Normalization::None && self.normalize(a) == self.normalize(b))
+    }
+}
@@ -0,0 +1,220 @@
+//
+// Deserialization of the query outputs into application types.
+//
+// The outputs of a query are deserialized straight from their `Val` values, so a decimal keeps
+// its exact value when it is deserialized into a `Decimal` field. An error reports the path of
+// the missing or mistyped output in the notation of `OutputReader::find_path`, e.g. "items[1].name".
+//
+use serde::de::{ self, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor };
+use serde::forward_to_deserialize_any;
+use std::fmt;
+
+use crate::{ decimal, Decimal, Entry, Val };
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputTypeError {
+    /// The path of the offending output, e.g. "address.city" or "items[1].name".
+    /// Empty when the error is about the outputs as a whole, e.g. a missing top level output.
+    pub path: String,
+
+    /// A human readable description of the error, e.g. "missing field `result`"
+    pub message: String,
+}
+
+impl OutputTypeError {
+    // Prefixes the path with the key of the enclosing object.
+    fn within_key(mut self, key: &str) -> Self {
+        self.path = match self.path.as_str() {
+            "" => key.to_string(),
+            path if path.starts_with('[') => format!("{}{}", key, path),
+            path => format!("{}.{}", key, path),
+        };
+        self
+    }
+
+    // Prefixes the path with the index of the enclosing array.
+    fn within_index(mut self, index: usize) -> Self {
+        self.path = match self.path.as_str() {
+            "" => format!("[{}]", index),
+            path if path.starts_with('[') => format!("[{}]{}", index, path),
+            path => format!("[{}].{}", index, path),
+        };
+        self
+    }
+}
+
+impl de::Error for OutputTypeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        OutputTypeError { path: String::new(), message: msg.to_string() }
+    }
+}
+
+impl fmt::Display for OutputTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "output '{}': {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for OutputTypeError {}
+
+pub(crate) struct ValDeserializer {
+    val: Val,
+}
+
+impl ValDeserializer {
+    pub(crate) fn new(val: Val) -> Self {
+        ValDeserializer { val }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValDeserializer {
+    type Error = OutputTypeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.val {
+            Val::Str(s) => visitor.visit_string(s),
+            Val::Int(i) => visitor.visit_i64(i),
+            Val::Bool(b) => visitor.visit_bool(b),
+            // The plain notation of a decimal always parses as a float
+            Val::Decimal(d) => visitor.visit_f64(d.to_string().parse().unwrap()),
+            Val::Array(items) => visitor.visit_seq(SeqDeserializer { items: items.into_iter().enumerate() }),
+            Val::Object(entries) => visitor.visit_map(MapDeserializer { entries: entries.into_iter(), current: None }),
+            Val::Null => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.val {
+            Val::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    // A decimal or an integer deserializes into a `Decimal` field exactly.
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        let is_decimal = name == decimal::SERDE_NAME;
+        match self.val {
+            Val::Decimal(d) if is_decimal => visitor.visit_map(DecimalDeserializer { decimal: d, field: 0 }),
+            Val::Int(i) if is_decimal => {
+                visitor.visit_map(DecimalDeserializer { decimal: Decimal::from_i64(i), field: 0 })
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // A string deserializes into a unit variant of the same name.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        match self.val {
+            Val::Str(s) => visitor.visit_enum(s.into_deserializer()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+struct SeqDeserializer {
+    items: std::iter::Enumerate<std::vec::IntoIter<Val>>,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+    type Error = OutputTypeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        match self.items.next() {
+            Some((index, item)) => {
+                seed.deserialize(ValDeserializer::new(item))
+                    .map(Some)
+                    .map_err(|err| err.within_index(index))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct MapDeserializer {
+    entries: std::vec::IntoIter<Entry>,
+    current: Option<Entry>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+    type Error = OutputTypeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some(entry) => {
+                let key = seed.deserialize(entry.key.as_str().into_deserializer())?;
+                self.current = Some(entry);
+                Ok(Some(key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let entry = self.current.take().ok_or_else(|| de::Error::custom("value is missing"))?;
+        seed.deserialize(ValDeserializer::new(entry.val)).map_err(|err| err.within_key(&entry.key))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+// Presents a decimal as the fields of the `Decimal` struct.
+struct DecimalDeserializer {
+    decimal: Decimal,
+    field: usize,
+}
+
+impl<'de> MapAccess<'de> for DecimalDeserializer {
+    type Error = OutputTypeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        let key = match self.field {
+            0 => "units",
+            1 => "scale",
+            _ => {
+                return Ok(None);
+            }
+        };
+        seed.deserialize(key.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        self.field += 1;
+        match self.field {
+            1 => seed.deserialize(self.decimal.units().into_deserializer()),
+            _ => seed.deserialize(self.decimal.scale().into_deserializer()),
+        }
+    }
+}